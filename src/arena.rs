@@ -0,0 +1,521 @@
+//! Fixed-capacity arena with generational handles.
+
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::iter::{Enumerate, FusedIterator};
+use core::marker::PhantomData;
+use core::{fmt, mem, ops, slice};
+
+use crate::{CapacityError, StaticVec};
+
+/// Counter stored in every slot of a [`StaticArena`] and in every [`Index`] to it.
+///
+/// A narrower generation makes the handles smaller, but retires slots sooner.
+pub trait Generation: Copy + Eq + Ord + Hash + fmt::Debug {
+    const FIRST: Self;
+
+    /// Returns the following generation, or `None` once the counter is exhausted.
+    fn next(self) -> Option<Self>;
+}
+
+macro_rules! impl_generation {
+    ($($ty:ty),*) => {
+        $(
+            impl Generation for $ty {
+                const FIRST: Self = 0;
+
+                fn next(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+            }
+        )*
+    };
+}
+
+impl_generation!(u8, u16, u32, u64, usize);
+
+/// Handle to a value in a [`StaticArena`].
+///
+/// Once the value is removed, the handle no longer resolves, even if its slot is reused.
+pub struct Index<T, G = u32> {
+    slot: usize,
+    generation: G,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T, G: Generation> Index<T, G> {
+    /// Returns the slot the handle points to.
+    pub fn slot(&self) -> usize {
+        self.slot
+    }
+
+    pub fn generation(&self) -> G {
+        self.generation
+    }
+}
+
+impl<T, G: Copy> Clone for Index<T, G> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, G: Copy> Copy for Index<T, G> {}
+
+impl<T, G: PartialEq> PartialEq for Index<T, G> {
+    fn eq(&self, other: &Self) -> bool {
+        self.slot == other.slot && self.generation == other.generation
+    }
+}
+
+impl<T, G: Eq> Eq for Index<T, G> {}
+
+impl<T, G: PartialOrd> PartialOrd for Index<T, G> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.slot.cmp(&other.slot) {
+            Ordering::Equal => self.generation.partial_cmp(&other.generation),
+            ord => Some(ord),
+        }
+    }
+}
+
+impl<T, G: Ord> Ord for Index<T, G> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.slot
+            .cmp(&other.slot)
+            .then_with(|| self.generation.cmp(&other.generation))
+    }
+}
+
+impl<T, G: Hash> Hash for Index<T, G> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.slot.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T, G: fmt::Debug> fmt::Debug for Index<T, G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Index")
+            .field("slot", &self.slot)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+enum Slot<T> {
+    /// Holds the next vacant slot, or the end of the storage.
+    Vacant(usize),
+    Occupied(T),
+}
+
+#[derive(Clone)]
+struct Entry<T, G> {
+    generation: G,
+    slot: Slot<T>,
+}
+
+/// Fixed-capacity arena, handing out generational [`Index`] handles.
+///
+/// Inserts and removals are O(1). The generation of a slot is bumped whenever its value is
+/// removed, so stale handles return `None` instead of aliasing a newer value. A slot whose
+/// generation is exhausted is retired and never reused, which lowers the capacity by one.
+pub struct StaticArena<T, const N: usize, G = u32> {
+    entries: StaticVec<Entry<T, G>, N>,
+    len: usize,
+    // head of the free list, `entries.len()` if no slot is vacant
+    next: usize,
+}
+
+impl<T, const N: usize, G: Generation> StaticArena<T, N, G> {
+    pub const fn new() -> Self {
+        Self {
+            entries: StaticVec::new(),
+            len: 0,
+            next: 0,
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    fn entry(&self, index: Index<T, G>) -> Option<&Entry<T, G>> {
+        self.entries
+            .get(index.slot)
+            .filter(|it| it.generation == index.generation)
+    }
+
+    pub fn contains(&self, index: Index<T, G>) -> bool {
+        matches!(
+            self.entry(index),
+            Some(Entry {
+                slot: Slot::Occupied(_),
+                ..
+            })
+        )
+    }
+
+    pub fn get(&self, index: Index<T, G>) -> Option<&T> {
+        match self.entry(index) {
+            Some(Entry {
+                slot: Slot::Occupied(value),
+                ..
+            }) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, index: Index<T, G>) -> Option<&mut T> {
+        match self.entries.as_mut_slice().get_mut(index.slot) {
+            Some(Entry {
+                generation,
+                slot: Slot::Occupied(value),
+            }) if *generation == index.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Inserts `value` and returns its handle.
+    ///
+    /// If there is no vacant slot left, the value is handed back in the error.
+    pub fn insert(&mut self, value: T) -> Result<Index<T, G>, CapacityError<T>> {
+        let slot = self.next;
+        let generation = if slot == self.entries.len() {
+            let entry = Entry {
+                generation: G::FIRST,
+                slot: Slot::Occupied(value),
+            };
+            if let Err(err) = self.entries.push(entry) {
+                match err.into_inner().slot {
+                    Slot::Occupied(value) => return Err(CapacityError::new(value)),
+                    Slot::Vacant(_) => unreachable!(),
+                }
+            }
+            self.next = slot + 1;
+            G::FIRST
+        } else {
+            let entry = &mut self.entries.as_mut_slice()[slot];
+            match mem::replace(&mut entry.slot, Slot::Occupied(value)) {
+                Slot::Vacant(next) => self.next = next,
+                Slot::Occupied(_) => unreachable!("free list points to an occupied slot"),
+            }
+            entry.generation
+        };
+        self.len += 1;
+        Ok(Index {
+            slot,
+            generation,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Removes the value behind `index` and returns it, invalidating all handles to it.
+    pub fn remove(&mut self, index: Index<T, G>) -> Option<T> {
+        if !self.contains(index) {
+            return None;
+        }
+        let end = self.entries.len();
+        let entry = &mut self.entries.as_mut_slice()[index.slot];
+        let value = match entry.generation.next() {
+            Some(generation) => {
+                entry.generation = generation;
+                let value = mem::replace(&mut entry.slot, Slot::Vacant(self.next));
+                self.next = index.slot;
+                value
+            }
+            // the generation is exhausted, so the slot is left out of the free list for good
+            None => mem::replace(&mut entry.slot, Slot::Vacant(end)),
+        };
+        self.len -= 1;
+        match value {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant(_) => unreachable!(),
+        }
+    }
+
+    /// Keeps only the values for which `f` returns `true`.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Index<T, G>, &mut T) -> bool,
+    {
+        for slot in 0..self.entries.len() {
+            let entry = &mut self.entries.as_mut_slice()[slot];
+            let index = Index {
+                slot,
+                generation: entry.generation,
+                _marker: PhantomData,
+            };
+            let keep = match &mut entry.slot {
+                Slot::Occupied(value) => f(index, value),
+                Slot::Vacant(_) => true,
+            };
+            if !keep {
+                self.remove(index);
+            }
+        }
+    }
+
+    /// Removes all values, invalidating all handles.
+    pub fn clear(&mut self) {
+        self.retain(|_, _| false);
+    }
+
+    /// Returns an iterator over the handles and values, in the slot order.
+    pub fn iter(&self) -> Iter<'_, T, G> {
+        Iter {
+            inner: self.entries.iter().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the handles and mutable values, in the slot order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, G> {
+        IterMut {
+            inner: self.entries.iter_mut().enumerate(),
+            len: self.len,
+        }
+    }
+}
+
+/// Iterator over the handles and values of a [`StaticArena`].
+pub struct Iter<'a, T, G> {
+    inner: Enumerate<slice::Iter<'a, Entry<T, G>>>,
+    len: usize,
+}
+
+impl<'a, T, G: Generation> Iterator for Iter<'a, T, G> {
+    type Item = (Index<T, G>, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (slot, entry) in self.inner.by_ref() {
+            if let Slot::Occupied(value) = &entry.slot {
+                self.len -= 1;
+                let index = Index {
+                    slot,
+                    generation: entry.generation,
+                    _marker: PhantomData,
+                };
+                return Some((index, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T, G: Generation> DoubleEndedIterator for Iter<'a, T, G> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some((slot, entry)) = self.inner.next_back() {
+            if let Slot::Occupied(value) = &entry.slot {
+                self.len -= 1;
+                let index = Index {
+                    slot,
+                    generation: entry.generation,
+                    _marker: PhantomData,
+                };
+                return Some((index, value));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T, G: Generation> ExactSizeIterator for Iter<'a, T, G> {}
+
+impl<'a, T, G: Generation> FusedIterator for Iter<'a, T, G> {}
+
+impl<'a, T, G> Clone for Iter<'a, T, G> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            len: self.len,
+        }
+    }
+}
+
+/// Iterator over the handles and mutable values of a [`StaticArena`].
+pub struct IterMut<'a, T, G> {
+    inner: Enumerate<slice::IterMut<'a, Entry<T, G>>>,
+    len: usize,
+}
+
+impl<'a, T, G: Generation> Iterator for IterMut<'a, T, G> {
+    type Item = (Index<T, G>, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (slot, entry) in self.inner.by_ref() {
+            if let Slot::Occupied(value) = &mut entry.slot {
+                self.len -= 1;
+                let index = Index {
+                    slot,
+                    generation: entry.generation,
+                    _marker: PhantomData,
+                };
+                return Some((index, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T, G: Generation> DoubleEndedIterator for IterMut<'a, T, G> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some((slot, entry)) = self.inner.next_back() {
+            if let Slot::Occupied(value) = &mut entry.slot {
+                self.len -= 1;
+                let index = Index {
+                    slot,
+                    generation: entry.generation,
+                    _marker: PhantomData,
+                };
+                return Some((index, value));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T, G: Generation> ExactSizeIterator for IterMut<'a, T, G> {}
+
+impl<'a, T, G: Generation> FusedIterator for IterMut<'a, T, G> {}
+
+impl<T, const N: usize, G: Generation> Default for StaticArena<T, N, G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize, G: Clone> Clone for StaticArena<T, N, G> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            len: self.len,
+            next: self.next,
+        }
+    }
+}
+
+impl<T: fmt::Debug, const N: usize, G: Generation> fmt::Debug for StaticArena<T, N, G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const N: usize, G: Generation> ops::Index<Index<T, G>> for StaticArena<T, N, G> {
+    type Output = T;
+
+    fn index(&self, index: Index<T, G>) -> &Self::Output {
+        self.get(index).expect("invalid arena index")
+    }
+}
+
+impl<T, const N: usize, G: Generation> ops::IndexMut<Index<T, G>> for StaticArena<T, N, G> {
+    fn index_mut(&mut self, index: Index<T, G>) -> &mut Self::Output {
+        self.get_mut(index).expect("invalid arena index")
+    }
+}
+
+impl<'a, T, const N: usize, G: Generation> IntoIterator for &'a StaticArena<T, N, G> {
+    type Item = (Index<T, G>, &'a T);
+
+    type IntoIter = Iter<'a, T, G>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize, G: Generation> IntoIterator for &'a mut StaticArena<T, N, G> {
+    type Item = (Index<T, G>, &'a mut T);
+
+    type IntoIter = IterMut<'a, T, G>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use super::*;
+    use crate::test_util::DropCounter;
+
+    #[test]
+    fn stale_handles_do_not_resolve() {
+        let mut arena = StaticArena::<u8, 2>::new();
+        let a = arena.insert(1).unwrap();
+        let b = arena.insert(2).unwrap();
+        assert_eq!(arena.insert(3).unwrap_err().into_inner(), 3);
+        assert_eq!(arena.remove(a), Some(1));
+        assert_eq!(arena.remove(a), None);
+
+        // the slot is reused under the next generation
+        let c = arena.insert(3).unwrap();
+        assert_eq!((c.slot(), c.generation()), (a.slot(), a.generation() + 1));
+        assert!(!arena.contains(a) && arena.get(a).is_none());
+        arena[c] += 1;
+        assert_eq!((arena[b], arena[c], arena.len()), (2, 4, 2));
+        assert!(arena.get_mut(a).is_none());
+    }
+
+    #[test]
+    fn exhausted_slot_is_retired() {
+        let mut arena = StaticArena::<u8, 1, u8>::new();
+        let mut last = arena.insert(0).unwrap();
+        for i in 1..=255 {
+            arena.remove(last).unwrap();
+            last = arena.insert(i).unwrap();
+        }
+        assert_eq!(last.generation(), u8::MAX);
+        assert_eq!(arena.remove(last), Some(255));
+        assert!(!arena.contains(last));
+        // the only slot is gone for good
+        assert_eq!(arena.insert(0).unwrap_err().into_inner(), 0);
+        assert!(arena.is_empty());
+    }
+
+    #[test]
+    fn retain_and_clear_invalidate_handles() {
+        let drops = Cell::new(0);
+        let mut arena = StaticArena::<_, 4>::new();
+        let mut handles = StaticVec::<_, 4>::new();
+        for i in 0..4 {
+            let index = arena.insert((i, DropCounter::new(&drops))).unwrap();
+            handles.push(index).unwrap();
+        }
+        arena.retain(|_, (i, _)| *i % 2 == 0);
+        assert_eq!(drops.get(), 2);
+        assert!(arena
+            .iter()
+            .map(|(index, _)| index)
+            .eq([handles[0], handles[2]]));
+        assert!(arena.iter().all(|(index, it)| arena[index].0 == it.0));
+        assert!(!arena.contains(handles[1]));
+
+        arena.clear();
+        assert_eq!(drops.get(), 4);
+        assert!(handles.iter().all(|it| !arena.contains(*it)));
+        arena.insert((4, DropCounter::new(&drops))).unwrap();
+        drop(arena);
+        assert_eq!(drops.get(), 5);
+    }
+}
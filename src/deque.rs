@@ -0,0 +1,529 @@
+//! Fixed-capacity double-ended queue.
+
+use core::iter::FusedIterator;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::{fmt, ptr, slice};
+
+use crate::{CapacityError, StaticVec};
+
+/// Fixed-capacity ring buffer, using the same storage model as [`StaticVec`].
+///
+/// Pushing and popping at both ends is O(1). Converting from a `StaticVec` is O(1), converting
+/// back to a `StaticVec` is O(1) if the front element is at the start of the storage and O(N)
+/// otherwise.
+pub struct StaticDeque<T, const N: usize> {
+    // index of the first element within data
+    head: usize,
+    len: usize,
+    data: [MaybeUninit<T>; N],
+}
+
+impl<T, const N: usize> StaticDeque<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            head: 0,
+            len: 0,
+            data: [const { MaybeUninit::uninit() }; N],
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Maps a logical index (0 is the front) to an index within data.
+    fn to_physical(&self, idx: usize) -> usize {
+        let idx = self.head + idx;
+        if idx >= N {
+            idx - N
+        } else {
+            idx
+        }
+    }
+
+    pub fn push_back(&mut self, item: T) -> Result<(), CapacityError<T>> {
+        if self.len == N {
+            return Err(CapacityError::new(item));
+        }
+        let idx = self.to_physical(self.len);
+        self.data[idx].write(item);
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn push_front(&mut self, item: T) -> Result<(), CapacityError<T>> {
+        if self.len == N {
+            return Err(CapacityError::new(item));
+        }
+        self.head = self.to_physical(N - 1);
+        self.data[self.head].write(item);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Pushes `item` to the back, removing and returning the front element if the deque is full.
+    pub fn push_back_overwrite(&mut self, item: T) -> Option<T> {
+        if N == 0 {
+            return Some(item);
+        }
+        let evicted = if self.len == N {
+            self.pop_front()
+        } else {
+            None
+        };
+        let idx = self.to_physical(self.len);
+        self.data[idx].write(item);
+        self.len += 1;
+        evicted
+    }
+
+    /// Pushes `item` to the front, removing and returning the back element if the deque is full.
+    pub fn push_front_overwrite(&mut self, item: T) -> Option<T> {
+        if N == 0 {
+            return Some(item);
+        }
+        let evicted = if self.len == N { self.pop_back() } else { None };
+        self.head = self.to_physical(N - 1);
+        self.data[self.head].write(item);
+        self.len += 1;
+        evicted
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let idx = self.head;
+        self.head = self.to_physical(1);
+        self.len -= 1;
+        //safe as the element was initialized and is no longer tracked by head..head + len
+        unsafe { Some(self.data.get_unchecked(idx).assume_init_read()) }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let idx = self.to_physical(self.len);
+        //safe as the element was initialized and is no longer tracked by head..head + len
+        unsafe { Some(self.data.get_unchecked(idx).assume_init_read()) }
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&T> {
+        if idx >= self.len {
+            return None;
+        }
+        //safe as the elements head..head + len are initialized
+        unsafe {
+            Some(
+                self.data
+                    .get_unchecked(self.to_physical(idx))
+                    .assume_init_ref(),
+            )
+        }
+    }
+
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut T> {
+        if idx >= self.len {
+            return None;
+        }
+        let idx = self.to_physical(idx);
+        //safe as the elements head..head + len are initialized
+        unsafe { Some(self.data.get_unchecked_mut(idx).assume_init_mut()) }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.wrapping_sub(1))
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.wrapping_sub(1))
+    }
+
+    /// Returns the physical ranges of the front and the back part of the elements.
+    fn slice_ranges(&self) -> (core::ops::Range<usize>, core::ops::Range<usize>) {
+        if self.head + self.len <= N {
+            (self.head..self.head + self.len, 0..0)
+        } else {
+            (self.head..N, 0..self.head + self.len - N)
+        }
+    }
+
+    /// Returns the elements as two slices, which in order form the contents of the deque.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (a, b) = self.slice_ranges();
+        //safe as we ensure that the elements within both ranges are initialized
+        unsafe {
+            (
+                &*(&self.data[a] as *const [MaybeUninit<T>] as *const [T]),
+                &*(&self.data[b] as *const [MaybeUninit<T>] as *const [T]),
+            )
+        }
+    }
+
+    /// Returns the elements as two slices, which in order form the contents of the deque.
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (a, b) = self.slice_ranges();
+        let (data_b, data_a) = self.data.split_at_mut(a.start);
+        //safe as we ensure that the elements within both ranges are initialized
+        unsafe {
+            (
+                &mut *(&mut data_a[..a.end - a.start] as *mut [MaybeUninit<T>] as *mut [T]),
+                &mut *(&mut data_b[b] as *mut [MaybeUninit<T>] as *mut [T]),
+            )
+        }
+    }
+
+    /// Moves the elements to the start of the storage and returns them as a slice.
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if self.head + self.len > N {
+            // moving uninitialized slots around is fine, as they are never read
+            self.data.rotate_left(self.head);
+        } else if self.head != 0 {
+            //safe as the elements head..head + len are initialized, and ptr::copy allows overlap
+            unsafe {
+                let ptr = self.data.as_mut_ptr();
+                ptr::copy(ptr.add(self.head), ptr, self.len);
+            }
+        }
+        self.head = 0;
+        self.as_mut_slices().0
+    }
+
+    pub fn clear(&mut self) {
+        let (a, b) = self.as_mut_slices();
+        let (a, b): (*mut [T], *mut [T]) = (a, b);
+        // reset first, so a panicking destructor can never lead to a double drop
+        self.head = 0;
+        self.len = 0;
+        unsafe { drop_slices(a, b) };
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (a, b) = self.as_slices();
+        Iter {
+            front: a.iter(),
+            back: b.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (a, b) = self.as_mut_slices();
+        IterMut {
+            front: a.iter_mut(),
+            back: b.iter_mut(),
+        }
+    }
+}
+
+/// Drops both slices, even if dropping the first one panics.
+unsafe fn drop_slices<T>(a: *mut [T], b: *mut [T]) {
+    struct Dropper<T>(*mut [T]);
+
+    impl<T> Drop for Dropper<T> {
+        fn drop(&mut self) {
+            unsafe { ptr::drop_in_place(self.0) }
+        }
+    }
+
+    let _back = Dropper(b);
+    ptr::drop_in_place(a)
+}
+
+impl<T, const N: usize> Drop for StaticDeque<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Default for StaticDeque<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Clone for StaticDeque<T, N>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        let mut ret = Self::new();
+        for it in self {
+            //cannot fail, as both deques have the same capacity
+            let _ = ret.push_back(it.clone());
+        }
+        ret
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for StaticDeque<T, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const N: usize> PartialEq for StaticDeque<T, N>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T, const N: usize> core::ops::Index<usize> for StaticDeque<T, N> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("index out of bounds")
+    }
+}
+
+impl<T, const N: usize> core::ops::IndexMut<usize> for StaticDeque<T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("index out of bounds")
+    }
+}
+
+impl<T, const N: usize> From<StaticVec<T, N>> for StaticDeque<T, N> {
+    fn from(vec: StaticVec<T, N>) -> Self {
+        let vec = ManuallyDrop::new(vec);
+        Self {
+            head: 0,
+            len: vec.len,
+            //safe as vec is never dropped, so the ownership of its elements moves to the deque
+            data: unsafe { ptr::read(&vec.data) },
+        }
+    }
+}
+
+impl<T, const N: usize> From<StaticDeque<T, N>> for StaticVec<T, N> {
+    fn from(mut deque: StaticDeque<T, N>) -> Self {
+        deque.make_contiguous();
+        let deque = ManuallyDrop::new(deque);
+        //safe as the elements 0..len are initialized after make_contiguous, and deque is never
+        //dropped, so the ownership of its elements moves to the vec
+        unsafe { StaticVec::from_raw_parts(ptr::read(&deque.data), deque.len) }
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a StaticDeque<T, N> {
+    type Item = &'a T;
+
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut StaticDeque<T, N> {
+    type Item = &'a mut T;
+
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, const N: usize> IntoIterator for StaticDeque<T, N> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+macro_rules! two_slice_iter {
+    ($name:ident, $item:ty) => {
+        impl<'a, T> Iterator for $name<'a, T> {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                match self.front.next() {
+                    Some(it) => Some(it),
+                    None => {
+                        core::mem::swap(&mut self.front, &mut self.back);
+                        self.front.next()
+                    }
+                }
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                let len = self.front.len() + self.back.len();
+                (len, Some(len))
+            }
+        }
+
+        impl<'a, T> DoubleEndedIterator for $name<'a, T> {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                match self.back.next_back() {
+                    Some(it) => Some(it),
+                    None => self.front.next_back(),
+                }
+            }
+        }
+
+        impl<'a, T> ExactSizeIterator for $name<'a, T> {}
+
+        impl<'a, T> FusedIterator for $name<'a, T> {}
+    };
+}
+
+/// Iterator over references to the elements of a [`StaticDeque`].
+#[derive(Clone, Debug)]
+pub struct Iter<'a, T> {
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+two_slice_iter!(Iter, &'a T);
+
+/// Iterator over mutable references to the elements of a [`StaticDeque`].
+#[derive(Debug)]
+pub struct IterMut<'a, T> {
+    front: slice::IterMut<'a, T>,
+    back: slice::IterMut<'a, T>,
+}
+
+two_slice_iter!(IterMut, &'a mut T);
+
+/// By-value iterator over the elements of a [`StaticDeque`].
+#[derive(Clone)]
+pub struct IntoIter<T, const N: usize>(StaticDeque<T, N>);
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> FusedIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> fmt::Debug for IntoIter<T, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IntoIter").field(&self.0).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use super::*;
+    use crate::test_util::{counters, keys, DropCounter};
+
+    #[test]
+    fn push_and_pop_across_wrap() {
+        let mut deque = StaticDeque::<u8, 4>::new();
+        for i in 0..3 {
+            deque.push_back(i).unwrap();
+        }
+        assert_eq!(deque.pop_front(), Some(0));
+        assert_eq!(deque.pop_front(), Some(1));
+        deque.push_back(3).unwrap();
+        deque.push_back(4).unwrap();
+        deque.push_front(1).unwrap();
+        assert!(deque.push_back(5).is_err());
+        assert_eq!(deque.as_slices(), (&[1, 2, 3][..], &[4][..]));
+        assert_eq!(deque.push_back_overwrite(5), Some(1));
+        assert_eq!(deque.push_front_overwrite(0), Some(5));
+        assert!(deque.iter().eq(&[0, 2, 3, 4]));
+        assert!(deque.iter().rev().eq(&[4, 3, 2, 0]));
+        assert_eq!(deque.pop_back(), Some(4));
+        assert_eq!((deque.front(), deque.back()), (Some(&0), Some(&3)));
+    }
+
+    #[test]
+    fn make_contiguous_moves_to_start() {
+        let mut deque = StaticDeque::<u8, 4>::new();
+        for i in 0..3 {
+            deque.push_back(i).unwrap();
+        }
+        deque.pop_front();
+        assert_eq!(deque.make_contiguous(), &[1, 2]);
+        assert_eq!(deque.head, 0);
+
+        deque.push_front(0).unwrap();
+        deque.push_back(3).unwrap();
+        assert_eq!(deque.make_contiguous(), &[0, 1, 2, 3]);
+        assert_eq!(deque.as_slices(), (&[0, 1, 2, 3][..], &[][..]));
+    }
+
+    #[test]
+    fn into_vec_after_pop_front() {
+        let drops = Cell::new(0);
+        let mut deque = StaticDeque::<_, 4>::new();
+        for i in 0..3 {
+            deque.push_back((i, DropCounter::new(&drops))).unwrap();
+        }
+        drop(deque.pop_front());
+        let vec = StaticVec::from(deque);
+        assert_eq!(vec.iter().map(|it| it.0).sum::<u8>(), 3);
+        assert_eq!(vec.len(), 2);
+        drop(vec);
+        assert_eq!(drops.get(), 3);
+    }
+
+    #[test]
+    fn vec_round_trip_moves_ownership() {
+        let drops = Cell::new(0);
+        let mut deque = StaticDeque::<_, 8>::from(counters(&drops, 6));
+        drop(deque.pop_front());
+        drop(deque.pop_front());
+        deque.push_back((6, DropCounter::new(&drops))).unwrap();
+        deque.push_back((7, DropCounter::new(&drops))).unwrap();
+        deque.push_back((8, DropCounter::new(&drops))).unwrap();
+        // wraps around the end of the storage
+        deque.push_back((9, DropCounter::new(&drops))).unwrap();
+        let vec = StaticVec::from(deque);
+        assert_eq!(keys(&vec).as_slice(), &[2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(drops.get(), 2);
+        drop(vec);
+        assert_eq!(drops.get(), 10);
+    }
+}
@@ -0,0 +1,589 @@
+//! Fixed-capacity hash map.
+
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+use core::{fmt, mem};
+
+use crate::map::{Iter, IterMut};
+use crate::{CapacityError, IntoIter as VecIntoIter, StaticVec};
+
+/// 64-bit FNV-1a hasher, the default hasher of [`StaticHashMap`].
+///
+/// It is fast for small keys, but not resistant against keys chosen to collide.
+#[derive(Debug, Copy, Clone)]
+pub struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+pub type FnvBuildHasher = BuildHasherDefault<FnvHasher>;
+
+/// Slot of the index table, pointing into the entries.
+#[derive(Debug, Copy, Clone)]
+struct Slot {
+    hash: usize,
+    idx: usize,
+}
+
+impl Slot {
+    const EMPTY: Slot = Slot {
+        hash: 0,
+        idx: usize::MAX,
+    };
+
+    fn is_empty(&self) -> bool {
+        self.idx == usize::MAX
+    }
+}
+
+/// Table mapping hashes to indices of densely stored entries, using Robin Hood linear probing
+/// and backward shift deletion.
+///
+/// It has `2 * N` slots for at most `N` entries, so the load factor stays at or below 50% and
+/// the probe sequences stay short even when the entries are full. The slots are stored in pairs,
+/// as the slot count cannot be computed from `N` in a type.
+#[derive(Debug, Clone)]
+pub(crate) struct IndexTable<const N: usize> {
+    slots: [[Slot; 2]; N],
+}
+
+impl<const N: usize> IndexTable<N> {
+    const SLOTS: usize = 2 * N;
+
+    pub(crate) const fn new() -> Self {
+        Self {
+            slots: [[Slot::EMPTY; 2]; N],
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.slots = [[Slot::EMPTY; 2]; N];
+    }
+
+    fn slots(&self) -> &[Slot] {
+        self.slots.as_flattened()
+    }
+
+    fn slots_mut(&mut self) -> &mut [Slot] {
+        self.slots.as_flattened_mut()
+    }
+
+    /// Slot a hash would ideally be stored in.
+    fn desired(hash: usize) -> usize {
+        hash % Self::SLOTS
+    }
+
+    /// Distance of the slot at `pos` from the ideal slot of `hash`.
+    fn distance(pos: usize, hash: usize) -> usize {
+        (pos + Self::SLOTS - Self::desired(hash)) % Self::SLOTS
+    }
+
+    fn next(pos: usize) -> usize {
+        if pos + 1 == Self::SLOTS {
+            0
+        } else {
+            pos + 1
+        }
+    }
+
+    /// Returns the entry index stored at `pos`.
+    pub(crate) fn idx(&self, pos: usize) -> usize {
+        self.slots()[pos].idx
+    }
+
+    /// Returns the position of the slot with `hash` whose entry satisfies `is_match`.
+    pub(crate) fn find<F>(&self, hash: usize, mut is_match: F) -> Option<usize>
+    where
+        F: FnMut(usize) -> bool,
+    {
+        if N == 0 {
+            return None;
+        }
+        let mut pos = Self::desired(hash);
+        for dist in 0..Self::SLOTS {
+            let slot = &self.slots()[pos];
+            // the entry would have taken over any slot closer to its ideal slot
+            if slot.is_empty() || Self::distance(pos, slot.hash) < dist {
+                return None;
+            }
+            if slot.hash == hash && is_match(slot.idx) {
+                return Some(pos);
+            }
+            pos = Self::next(pos);
+        }
+        None
+    }
+
+    /// Stores the entry index `idx` under `hash`. The table must have a free slot.
+    pub(crate) fn insert(&mut self, hash: usize, idx: usize) {
+        let mut slot = Slot { hash, idx };
+        let mut pos = Self::desired(hash);
+        let mut dist = 0;
+        loop {
+            let cur = &mut self.slots_mut()[pos];
+            if cur.is_empty() {
+                *cur = slot;
+                return;
+            }
+            // robin hood: the entry further away from its ideal slot keeps the slot
+            let cur_dist = Self::distance(pos, cur.hash);
+            if cur_dist < dist {
+                mem::swap(cur, &mut slot);
+                dist = cur_dist;
+            }
+            pos = Self::next(pos);
+            dist += 1;
+        }
+    }
+
+    /// Empties the slot at `pos`, shifting the following slots back.
+    pub(crate) fn remove(&mut self, mut pos: usize) {
+        loop {
+            let next = Self::next(pos);
+            let slot = self.slots()[next];
+            if slot.is_empty() || Self::distance(next, slot.hash) == 0 {
+                self.slots_mut()[pos] = Slot::EMPTY;
+                return;
+            }
+            self.slots_mut()[pos] = slot;
+            pos = next;
+        }
+    }
+
+    /// Points the slot of the entry with `hash` at index `from` to index `to`, after the entry
+    /// was moved.
+    pub(crate) fn relink(&mut self, hash: usize, from: usize, to: usize) {
+        //cannot fail, as the entry is stored in the table
+        let pos = self.find(hash, |idx| idx == from).unwrap();
+        self.slots_mut()[pos].idx = to;
+    }
+}
+
+/// Fixed-capacity hash map with a pluggable hasher.
+///
+/// The entries are stored densely in a [`StaticVec`], in insertion order until the first
+/// removal. They are found through an index table of `2 * N` slots using Robin Hood linear
+/// probing, so the table is at most half full. Removal shifts the following slots back instead of leaving tombstones, so lookups stay fast
+/// no matter how many entries were removed.
+pub struct StaticHashMap<K, V, const N: usize, S = FnvBuildHasher> {
+    entries: StaticVec<(K, V), N>,
+    table: IndexTable<N>,
+    hasher: S,
+}
+
+impl<K, V, const N: usize, S: Default> StaticHashMap<K, V, N, S> {
+    pub fn new() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, const N: usize, S> StaticHashMap<K, V, N, S> {
+    pub const fn with_hasher(hasher: S) -> Self {
+        Self {
+            entries: StaticVec::new(),
+            table: IndexTable::new(),
+            hasher,
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.table.clear();
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(&self.entries)
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut::new(&mut self.entries)
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator + '_ {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator + '_ {
+        self.entries.iter().map(|(_, v)| v)
+    }
+
+    pub fn values_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut V> + ExactSizeIterator + '_ {
+        self.entries.iter_mut().map(|(_, v)| v)
+    }
+}
+
+impl<K, V, const N: usize, S> StaticHashMap<K, V, N, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn hash<Q: Hash + ?Sized>(&self, key: &Q) -> usize {
+        self.hasher.hash_one(key) as usize
+    }
+
+    /// Returns the position of the slot of `key` within the index table.
+    fn find<Q>(&self, hash: usize, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.table
+            .find(hash, |idx| self.entries[idx].0.borrow() == key)
+    }
+
+    fn find_index<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let pos = self.find(self.hash(key), key)?;
+        Some(self.table.idx(pos))
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find_index(key).is_some()
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find_index(key)?;
+        let (k, v) = &self.entries[idx];
+        Some((k, v))
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find_index(key)?;
+        Some(&mut self.entries.as_mut_slice()[idx].1)
+    }
+
+    /// Inserts `value` under `key`, returning the previous value of `key`.
+    ///
+    /// If the key is new and the map is full, the entry is handed back in the error.
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, CapacityError<(K, V)>> {
+        let hash = self.hash(&key);
+        if let Some(pos) = self.find(hash, &key) {
+            let idx = self.table.idx(pos);
+            return Ok(Some(mem::replace(
+                &mut self.entries.as_mut_slice()[idx].1,
+                value,
+            )));
+        }
+        let idx = self.entries.len();
+        self.entries.push((key, value))?;
+        self.table.insert(hash, idx);
+        Ok(None)
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let pos = self.find(self.hash(key), key)?;
+        let idx = self.table.idx(pos);
+        self.table.remove(pos);
+
+        let entry = self.entries.swap_remove(idx);
+        // the last entry was moved into the gap, so its slot has to follow
+        let moved = self.entries.len();
+        if idx != moved {
+            let hash = self.hash(&self.entries[idx].0);
+            self.table.relink(hash, moved, idx);
+        }
+        Some(entry)
+    }
+
+    /// Keeps only the entries for which `f` returns `true`.
+    ///
+    /// If `f` panics, the entries it was not called for yet are kept.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let len = self.entries.len();
+        let guard = RebuildGuard { map: self };
+        guard.map.entries.retain_mut(|(k, v)| f(k, v));
+        if guard.map.entries.len() == len {
+            mem::forget(guard);
+        }
+    }
+
+    /// Refills the index table from the entries, after they were moved.
+    fn rebuild(&mut self) {
+        self.table.clear();
+        for idx in 0..self.entries.len() {
+            let hash = self.hash(&self.entries[idx].0);
+            self.table.insert(hash, idx);
+        }
+    }
+}
+
+/// Rebuilds the index table of the map, on success or unwind, so it points at the right entries
+/// even if `retain` panics after moving some of them.
+struct RebuildGuard<'a, K: Hash + Eq, V, const N: usize, S: BuildHasher> {
+    map: &'a mut StaticHashMap<K, V, N, S>,
+}
+
+impl<'a, K: Hash + Eq, V, const N: usize, S: BuildHasher> Drop for RebuildGuard<'a, K, V, N, S> {
+    fn drop(&mut self) {
+        self.map.rebuild();
+    }
+}
+
+impl<K, V, const N: usize, S: Default> Default for StaticHashMap<K, V, N, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Clone, V: Clone, const N: usize, S: Clone> Clone for StaticHashMap<K, V, N, S> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            table: self.table.clone(),
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, const N: usize, S> fmt::Debug for StaticHashMap<K, V, N, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, const N: usize, S> PartialEq for StaticHashMap<K, V, N, S>
+where
+    K: Hash + Eq,
+    V: PartialEq,
+    S: BuildHasher,
+{
+    /// Maps are equal if they contain the same entries, in any order.
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K, V, const N: usize, S> Eq for StaticHashMap<K, V, N, S>
+where
+    K: Hash + Eq,
+    V: Eq,
+    S: BuildHasher,
+{
+}
+
+impl<K, V, Q, const N: usize, S> core::ops::Index<&Q> for StaticHashMap<K, V, N, S>
+where
+    K: Borrow<Q> + Hash + Eq,
+    Q: Hash + Eq + ?Sized,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &Self::Output {
+        self.get(key).expect("key not found")
+    }
+}
+
+impl<'a, K, V, const N: usize, S> IntoIterator for &'a StaticHashMap<K, V, N, S> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, const N: usize, S> IntoIterator for &'a mut StaticHashMap<K, V, N, S> {
+    type Item = (&'a K, &'a mut V);
+
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, const N: usize, S> IntoIterator for StaticHashMap<K, V, N, S> {
+    type Item = (K, V);
+
+    type IntoIter = VecIntoIter<(K, V), N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::panics;
+
+    /// Pseudo-random numbers from a linear congruential generator.
+    fn next(seed: &mut u32) -> u32 {
+        *seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        *seed >> 16
+    }
+
+    /// Checks that `table` holds exactly the `(hash, idx)` pairs of `model`.
+    fn check<const N: usize>(table: &IndexTable<N>, model: &[(usize, usize)]) {
+        let used = table.slots().iter().filter(|slot| !slot.is_empty()).count();
+        assert_eq!(used, model.len());
+        for &(hash, idx) in model {
+            let pos = table.find(hash, |it| it == idx).unwrap();
+            assert_eq!(table.idx(pos), idx);
+        }
+    }
+
+    #[test]
+    fn table_wraps_and_shifts_back() {
+        let mut table = IndexTable::<4>::new();
+        // all of them want the last slot, so they wrap around to the first ones
+        for (idx, hash) in [7, 15, 23, 0].into_iter().enumerate() {
+            table.insert(hash, idx);
+        }
+        check(&table, &[(7, 0), (15, 1), (23, 2), (0, 3)]);
+        assert_eq!(table.find(31, |_| true), None);
+
+        table.remove(table.find(7, |_| true).unwrap());
+        check(&table, &[(15, 1), (23, 2), (0, 3)]);
+        // the entries behind the removed one moved back towards their ideal slot
+        assert_eq!(table.find(15, |_| true), Some(7));
+        assert_eq!(table.find(0, |_| true), Some(1));
+
+        table.relink(23, 2, 0);
+        check(&table, &[(15, 1), (23, 0), (0, 3)]);
+        table.clear();
+        check(&table, &[]);
+    }
+
+    #[test]
+    fn table_matches_model() {
+        let mut table = IndexTable::<4>::new();
+        let mut model = StaticVec::<(usize, usize), 4>::new();
+        let mut seed = 1;
+        for _ in 0..2000 {
+            let hash = next(&mut seed) as usize % 24;
+            if model.len() == 4 || (!model.is_empty() && next(&mut seed) % 3 == 0) {
+                let i = next(&mut seed) as usize % model.len();
+                let (hash, idx) = model[i];
+                table.remove(table.find(hash, |it| it == idx).unwrap());
+                model.swap_remove(i);
+            } else {
+                let idx = (0..4).find(|i| model.iter().all(|it| it.1 != *i)).unwrap();
+                table.insert(hash, idx);
+                model.push((hash, idx)).unwrap();
+            }
+            check(&table, &model);
+        }
+    }
+
+    #[test]
+    fn map_matches_model() {
+        let mut map = StaticHashMap::<u8, u32, 8>::new();
+        let mut model = [None; 16];
+        let mut seed = 7;
+        for _ in 0..2000 {
+            let key = next(&mut seed) as u8 % 16;
+            let value = next(&mut seed);
+            let slot = &mut model[key as usize];
+            if next(&mut seed) % 2 == 0 {
+                assert_eq!(map.remove(&key), slot.take());
+            } else if slot.is_some() || map.len() < 8 {
+                assert_eq!(map.insert(key, value).ok(), Some(slot.replace(value)));
+            } else {
+                assert_eq!(
+                    map.insert(key, value).unwrap_err().into_inner(),
+                    (key, value)
+                );
+            }
+            assert_eq!(map.len(), model.iter().flatten().count());
+            for (key, value) in model.iter().enumerate() {
+                assert_eq!(map.get(&(key as u8)), value.as_ref());
+            }
+        }
+    }
+
+    #[test]
+    fn retain_keeps_table_after_panic() {
+        let mut map = StaticHashMap::<u8, u8, 8>::new();
+        for i in 0..8 {
+            map.insert(i, i * 10).unwrap();
+        }
+        let mut calls = 0;
+        assert!(panics(|| map.retain(|k, _| {
+            calls += 1;
+            assert_ne!(calls, 5);
+            k % 2 == 1
+        })));
+        assert_eq!(map.len(), 6);
+        for i in 0..8 {
+            let kept = i % 2 == 1 || i >= 4;
+            assert_eq!(map.get(&i), kept.then_some(&(i * 10)));
+        }
+        assert_eq!(map.insert(7, 0).ok(), Some(Some(70)));
+        assert_eq!(map.len(), 6);
+    }
+}
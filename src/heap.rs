@@ -0,0 +1,325 @@
+//! Fixed-capacity priority queue.
+
+use core::marker::PhantomData;
+use core::{fmt, slice};
+
+use crate::{CapacityError, StaticVec};
+
+/// Decides which element of a [`StaticBinaryHeap`] is on top.
+pub trait HeapKind {
+    /// Returns `true` if `a` has to be above `b` in the heap.
+    fn is_above<T: Ord>(a: &T, b: &T) -> bool;
+}
+
+/// The greatest element is on top.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Max;
+
+/// The smallest element is on top.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Min;
+
+impl HeapKind for Max {
+    fn is_above<T: Ord>(a: &T, b: &T) -> bool {
+        a > b
+    }
+}
+
+impl HeapKind for Min {
+    fn is_above<T: Ord>(a: &T, b: &T) -> bool {
+        a < b
+    }
+}
+
+/// Fixed-capacity binary heap, stored in a [`StaticVec`].
+pub struct StaticBinaryHeap<T, const N: usize, K = Max> {
+    data: StaticVec<T, N>,
+    _kind: PhantomData<K>,
+}
+
+impl<T: Ord, const N: usize, K: HeapKind> StaticBinaryHeap<T, N, K> {
+    pub const fn new() -> Self {
+        Self {
+            data: StaticVec::new(),
+            _kind: PhantomData,
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the top element.
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Returns a mutable reference to the top element. The heap is restored when the returned
+    /// guard is dropped, if the element was modified.
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T, N, K>> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(PeekMut {
+            heap: self,
+            sift: false,
+        })
+    }
+
+    /// Pushes `item` into the heap.
+    ///
+    /// Panics if the heap is full.
+    pub fn push(&mut self, item: T) {
+        if self.try_push(item).is_err() {
+            panic!("heap exceeds capacity {}", N);
+        }
+    }
+
+    /// Pushes `item` into the heap, or returns it back if the heap is full.
+    pub fn try_push(&mut self, item: T) -> Result<(), CapacityError<T>> {
+        self.data.push(item)?;
+        self.sift_up(self.len() - 1);
+        Ok(())
+    }
+
+    /// Removes the top element and returns it.
+    pub fn pop(&mut self) -> Option<T> {
+        let mut item = self.data.pop()?;
+        if let Some(top) = self.data.first_mut() {
+            core::mem::swap(&mut item, top);
+            self.sift_down(0);
+        }
+        Some(item)
+    }
+
+    /// Returns the elements in the heap order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Returns an iterator over the elements in the heap order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    /// Returns the underlying vec, with the elements in the heap order.
+    pub fn into_vec(self) -> StaticVec<T, N> {
+        self.data
+    }
+
+    /// Returns the underlying vec, sorted in ascending order.
+    pub fn into_sorted_vec(self) -> StaticVec<T, N> {
+        let mut data = self.data;
+        data.sort_unstable();
+        data
+    }
+
+    fn sift_up(&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if !K::is_above(&self.data[pos], &self.data[parent]) {
+                break;
+            }
+            self.data.swap(pos, parent);
+            pos = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut pos: usize) {
+        let len = self.len();
+        loop {
+            let left = 2 * pos + 1;
+            if left >= len {
+                break;
+            }
+            let right = left + 1;
+            let child = if right < len && K::is_above(&self.data[right], &self.data[left]) {
+                right
+            } else {
+                left
+            };
+            if !K::is_above(&self.data[child], &self.data[pos]) {
+                break;
+            }
+            self.data.swap(pos, child);
+            pos = child;
+        }
+    }
+
+    fn rebuild(&mut self) {
+        let mut pos = self.len() / 2;
+        while pos > 0 {
+            pos -= 1;
+            self.sift_down(pos);
+        }
+    }
+}
+
+/// Guard returned by [`StaticBinaryHeap::peek_mut`], which restores the heap on drop.
+pub struct PeekMut<'a, T: Ord, const N: usize, K: HeapKind> {
+    heap: &'a mut StaticBinaryHeap<T, N, K>,
+    // set once the top element is handed out mutably
+    sift: bool,
+}
+
+impl<'a, T: Ord, const N: usize, K: HeapKind> PeekMut<'a, T, N, K> {
+    /// Removes the peeked element from the heap and returns it.
+    pub fn pop(mut this: Self) -> T {
+        // the element is removed, so there is nothing to sift on drop
+        this.sift = false;
+        //cannot fail, as the guard is only created for a non-empty heap
+        this.heap.pop().unwrap()
+    }
+}
+
+impl<'a, T: Ord, const N: usize, K: HeapKind> core::ops::Deref for PeekMut<'a, T, N, K> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.heap.data[0]
+    }
+}
+
+impl<'a, T: Ord, const N: usize, K: HeapKind> core::ops::DerefMut for PeekMut<'a, T, N, K> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.sift = true;
+        &mut self.heap.data.as_mut_slice()[0]
+    }
+}
+
+impl<'a, T: Ord, const N: usize, K: HeapKind> Drop for PeekMut<'a, T, N, K> {
+    fn drop(&mut self) {
+        if self.sift {
+            self.heap.sift_down(0);
+        }
+    }
+}
+
+impl<'a, T: Ord + fmt::Debug, const N: usize, K: HeapKind> fmt::Debug for PeekMut<'a, T, N, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PeekMut").field(&self.heap.data[0]).finish()
+    }
+}
+
+impl<T: Ord, const N: usize, K: HeapKind> From<StaticVec<T, N>> for StaticBinaryHeap<T, N, K> {
+    /// Turns the vec into a heap in O(n).
+    fn from(data: StaticVec<T, N>) -> Self {
+        let mut heap = Self {
+            data,
+            _kind: PhantomData,
+        };
+        heap.rebuild();
+        heap
+    }
+}
+
+impl<T, const N: usize, K> From<StaticBinaryHeap<T, N, K>> for StaticVec<T, N> {
+    fn from(heap: StaticBinaryHeap<T, N, K>) -> Self {
+        heap.data
+    }
+}
+
+impl<T: Ord, const N: usize, K: HeapKind> Default for StaticBinaryHeap<T, N, K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize, K> Clone for StaticBinaryHeap<T, N, K> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            _kind: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug, const N: usize, K> fmt::Debug for StaticBinaryHeap<T, N, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.data.iter()).finish()
+    }
+}
+
+impl<'a, T, const N: usize, K> IntoIterator for &'a StaticBinaryHeap<T, N, K> {
+    type Item = &'a T;
+
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::panics;
+
+    fn drain<K: HeapKind>(mut heap: StaticBinaryHeap<u8, 16, K>) -> StaticVec<u8, 16> {
+        let mut out = StaticVec::new();
+        while let Some(it) = heap.pop() {
+            out.push(it).unwrap();
+        }
+        out
+    }
+
+    const ITEMS: [u8; 10] = [5, 1, 8, 3, 9, 3, 0, 7, 2, 6];
+
+    #[test]
+    fn pops_in_priority_order() {
+        let mut max = StaticBinaryHeap::<u8, 16>::new();
+        let mut min = StaticBinaryHeap::<u8, 16, Min>::new();
+        for it in ITEMS {
+            max.push(it);
+            min.push(it);
+        }
+        assert_eq!((max.peek(), min.peek()), (Some(&9), Some(&0)));
+        assert_eq!(drain(max).as_slice(), &[9, 8, 7, 6, 5, 3, 3, 2, 1, 0]);
+        assert_eq!(drain(min).as_slice(), &[0, 1, 2, 3, 3, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn from_vec_builds_heap() {
+        let heap = StaticBinaryHeap::<u8, 16, Min>::from(StaticVec::from_array(ITEMS));
+        assert_eq!(heap.len(), 10);
+        assert_eq!(
+            heap.clone().into_sorted_vec().as_slice(),
+            &[0, 1, 2, 3, 3, 5, 6, 7, 8, 9]
+        );
+        assert_eq!(drain(heap).as_slice(), &[0, 1, 2, 3, 3, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn peek_mut_restores_heap() {
+        let mut heap = StaticBinaryHeap::<u8, 16>::from(StaticVec::from_array(ITEMS));
+        *heap.peek_mut().unwrap() = 4;
+        assert_eq!(heap.peek(), Some(&8));
+        // only reading the top element leaves the heap as it is
+        assert_eq!(*heap.peek_mut().unwrap(), 8);
+        assert_eq!(PeekMut::pop(heap.peek_mut().unwrap()), 8);
+        assert_eq!(drain(heap).as_slice(), &[7, 6, 5, 4, 3, 3, 2, 1, 0]);
+        assert!(StaticBinaryHeap::<u8, 16>::new().peek_mut().is_none());
+    }
+
+    #[test]
+    fn full_heap_rejects_push() {
+        let mut heap = StaticBinaryHeap::<u8, 2>::new();
+        heap.push(1);
+        heap.try_push(2).unwrap();
+        assert_eq!(heap.try_push(3).unwrap_err().into_inner(), 3);
+        assert!(panics(|| heap.push(3)));
+        assert_eq!(heap.into_vec().as_slice(), &[2, 1]);
+    }
+}
@@ -0,0 +1,354 @@
+use core::iter::FusedIterator;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::ops::Range;
+use core::ptr;
+
+use crate::StaticVec;
+
+/// By-value iterator over the elements of a [`StaticVec`].
+pub struct IntoIter<T, const N: usize> {
+    data: [MaybeUninit<T>; N],
+    // elements start..end are initialized and not yet yielded
+    start: usize,
+    end: usize,
+}
+
+impl<T, const N: usize> IntoIter<T, N> {
+    pub(crate) fn new(vec: StaticVec<T, N>) -> Self {
+        let vec = ManuallyDrop::new(vec);
+        Self {
+            //safe as vec is never dropped, so the ownership of its elements moves to the iterator
+            data: unsafe { ptr::read(&vec.data) },
+            start: 0,
+            end: vec.len,
+        }
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        //safe as we ensure that start..end elements are initialized
+        unsafe { core::mem::transmute::<_, &[T]>(&self.data[self.start..self.end]) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        //safe as we ensure that start..end elements are initialized
+        unsafe { core::mem::transmute::<_, &mut [T]>(&mut self.data[self.start..self.end]) }
+    }
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            return None;
+        }
+        let idx = self.start;
+        self.start += 1;
+        //safe as the element was initialized and is no longer tracked by start..end
+        unsafe { Some(self.data.get_unchecked(idx).assume_init_read()) }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            return None;
+        }
+        self.end -= 1;
+        //safe as the element was initialized and is no longer tracked by start..end
+        unsafe { Some(self.data.get_unchecked(self.end).assume_init_read()) }
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> FusedIterator for IntoIter<T, N> {}
+
+#[cfg(feature = "nightly")]
+unsafe impl<T, const N: usize> core::iter::TrustedLen for IntoIter<T, N> {}
+
+impl<T, const N: usize> Clone for IntoIter<T, N>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        let src = self.as_slice();
+        //cannot fail, as the remaining elements came from a vec of the same capacity
+        let vec = StaticVec::<T, N>::from_fn(src.len(), |i| src[i].clone()).unwrap();
+        Self::new(vec)
+    }
+}
+
+impl<T, const N: usize> core::fmt::Debug for IntoIter<T, N>
+where
+    T: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("IntoIter").field(&self.as_slice()).finish()
+    }
+}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        let remaining: *mut [T] = self.as_mut_slice();
+        // mark everything as yielded first, so a panicking destructor can never lead to a double drop
+        self.start = self.end;
+        unsafe {
+            ptr::drop_in_place(remaining);
+        }
+    }
+}
+
+/// Draining iterator returned by [`StaticVec::drain`].
+pub struct Drain<'a, T, const N: usize> {
+    vec: &'a mut StaticVec<T, N>,
+    // elements start..end are initialized and not yet yielded
+    start: usize,
+    end: usize,
+    tail_start: usize,
+    tail_len: usize,
+}
+
+impl<'a, T, const N: usize> Drain<'a, T, N> {
+    pub(crate) fn new(vec: &'a mut StaticVec<T, N>, range: Range<usize>) -> Self {
+        let len = vec.len;
+        // the vec only keeps its head while draining, the tail is moved back on drop
+        vec.len = range.start;
+        Self {
+            vec,
+            start: range.start,
+            end: range.end,
+            tail_start: range.end,
+            tail_len: len - range.end,
+        }
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        //safe as we ensure that start..end elements are initialized
+        unsafe { core::mem::transmute::<_, &[T]>(&self.vec.data[self.start..self.end]) }
+    }
+}
+
+impl<'a, T, const N: usize> Iterator for Drain<'a, T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            return None;
+        }
+        let idx = self.start;
+        self.start += 1;
+        //safe as the element was initialized and is no longer tracked by start..end
+        unsafe { Some(self.vec.data.get_unchecked(idx).assume_init_read()) }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T, const N: usize> DoubleEndedIterator for Drain<'a, T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            return None;
+        }
+        self.end -= 1;
+        //safe as the element was initialized and is no longer tracked by start..end
+        unsafe { Some(self.vec.data.get_unchecked(self.end).assume_init_read()) }
+    }
+}
+
+impl<'a, T, const N: usize> ExactSizeIterator for Drain<'a, T, N> {}
+
+impl<'a, T, const N: usize> FusedIterator for Drain<'a, T, N> {}
+
+#[cfg(feature = "nightly")]
+unsafe impl<'a, T, const N: usize> core::iter::TrustedLen for Drain<'a, T, N> {}
+
+impl<'a, T, const N: usize> core::fmt::Debug for Drain<'a, T, N>
+where
+    T: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Drain").field(&self.as_slice()).finish()
+    }
+}
+
+impl<'a, T, const N: usize> Drop for Drain<'a, T, N> {
+    fn drop(&mut self) {
+        /// Moves the tail back behind the head, even if dropping the remaining elements panics.
+        struct MoveTail<'r, 'a, T, const N: usize>(&'r mut Drain<'a, T, N>);
+
+        impl<'r, 'a, T, const N: usize> Drop for MoveTail<'r, 'a, T, N> {
+            fn drop(&mut self) {
+                let drain = &mut *self.0;
+                let head = drain.vec.len;
+                unsafe {
+                    let ptr = drain.vec.data_mut_ptr();
+                    ptr::copy(ptr.add(drain.tail_start), ptr.add(head), drain.tail_len);
+                }
+                drain.vec.len = head + drain.tail_len;
+            }
+        }
+
+        let remaining = self.end - self.start;
+        let first = self.start;
+        self.start = self.end;
+        let guard = MoveTail(self);
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                guard.0.vec.data_mut_ptr().add(first),
+                remaining,
+            ));
+        }
+    }
+}
+
+/// Iterator returned by [`StaticVec::extract_if`].
+pub struct ExtractIf<'a, T, F, const N: usize>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    vec: &'a mut StaticVec<T, N>,
+    filter: F,
+    // next element to visit
+    idx: usize,
+    // end of the range to visit
+    end: usize,
+    // number of elements extracted so far
+    del: usize,
+    old_len: usize,
+}
+
+impl<'a, T, F, const N: usize> ExtractIf<'a, T, F, N>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    pub(crate) fn new(vec: &'a mut StaticVec<T, N>, range: Range<usize>, filter: F) -> Self {
+        let old_len = vec.len;
+        // the vec is kept empty while elements are moved around, drop restores the length
+        vec.len = 0;
+        Self {
+            vec,
+            filter,
+            idx: range.start,
+            end: range.end,
+            del: 0,
+            old_len,
+        }
+    }
+}
+
+impl<'a, T, F, const N: usize> Iterator for ExtractIf<'a, T, F, N>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.idx < self.end {
+            unsafe {
+                let ptr = self.vec.data_mut_ptr();
+                let cur = ptr.add(self.idx);
+                // idx is only advanced after the filter returns, so a panicking filter keeps
+                // the current element
+                let extracted = (self.filter)(&mut *cur);
+                self.idx += 1;
+                if extracted {
+                    self.del += 1;
+                    return Some(ptr::read(cur));
+                } else if self.del > 0 {
+                    ptr::copy_nonoverlapping(cur, cur.sub(self.del), 1);
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.end - self.idx))
+    }
+}
+
+impl<'a, T, F, const N: usize> Drop for ExtractIf<'a, T, F, N>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    fn drop(&mut self) {
+        if self.del > 0 {
+            unsafe {
+                let ptr = self.vec.data_mut_ptr();
+                ptr::copy(
+                    ptr.add(self.idx),
+                    ptr.add(self.idx - self.del),
+                    self.old_len - self.idx,
+                );
+            }
+        }
+        self.vec.len = self.old_len - self.del;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use crate::test_util::{counters, keys, panics, DropCounter};
+
+    #[test]
+    fn drain_moves_tail_back() {
+        let drops = Cell::new(0);
+        let mut vec = counters(&drops, 6);
+        let mut drain = vec.drain(1..4);
+        assert_eq!(drain.next().map(|it| it.0), Some(1));
+        drop(drain);
+        assert_eq!(keys(&vec).as_slice(), &[0, 4, 5]);
+        assert_eq!(drops.get(), 3);
+    }
+
+    #[test]
+    fn drain_moves_tail_back_after_panic() {
+        let drops = Cell::new(0);
+        let mut vec = counters(&drops, 6);
+        vec.as_mut_slice()[2].1 = DropCounter::panicking(&drops);
+        assert!(panics(|| drop(vec.drain(1..4))));
+        assert_eq!(keys(&vec).as_slice(), &[0, 4, 5]);
+        assert_eq!(drops.get(), 4);
+        drop(vec);
+        assert_eq!(drops.get(), 7);
+    }
+
+    #[test]
+    fn extract_if_keeps_unvisited() {
+        let drops = Cell::new(0);
+        let mut vec = counters(&drops, 6);
+        let mut extract = vec.extract_if(.., |it| it.0 % 2 == 1);
+        assert_eq!(extract.next().map(|it| it.0), Some(1));
+        drop(extract);
+        assert_eq!(keys(&vec).as_slice(), &[0, 2, 3, 4, 5]);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn extract_if_keeps_order_after_panic() {
+        let drops = Cell::new(0);
+        let mut vec = counters(&drops, 6);
+        assert!(panics(|| {
+            for _ in vec.extract_if(1.., |it| {
+                assert_ne!(it.0, 4);
+                it.0 % 2 == 1
+            }) {}
+        }));
+        // the element the filter panicked on and the ones after it are kept
+        assert_eq!(keys(&vec).as_slice(), &[0, 2, 4, 5]);
+        assert_eq!(drops.get(), 2);
+        drop(vec);
+        assert_eq!(drops.get(), 6);
+    }
+}
@@ -1,65 +1,235 @@
 #![no_std]
-#![allow(incomplete_features)]
-#![feature(maybe_uninit_uninit_array)]
-#![feature(generic_const_exprs)]
-#![feature(generic_arg_infer)]
+#![cfg_attr(feature = "nightly", feature(trusted_len))]
 
-use core::mem::MaybeUninit;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::ops::{Bound, Range, RangeBounds};
 use core::{ptr, slice};
 
-use either::Either;
+pub mod arena;
+pub mod deque;
+pub mod hash_map;
+pub mod heap;
+mod iter;
+pub mod linear_map;
+pub mod lru;
+pub mod map;
+pub mod pinned;
+pub mod policy;
+pub mod select;
+pub mod set;
+pub mod slab;
+mod string;
+#[cfg(test)]
+mod test_util;
+mod writer;
+
+pub use arena::StaticArena;
+pub use deque::StaticDeque;
+pub use hash_map::StaticHashMap;
+pub use heap::StaticBinaryHeap;
+pub use iter::{Drain, ExtractIf, IntoIter};
+pub use linear_map::LinearMap;
+pub use lru::StaticLru;
+pub use map::StaticMap;
+pub use pinned::PinnedVec;
+pub use policy::{OverflowPolicy, PolicyVec};
+pub use select::{
+    SelectAll, SelectPinned, SelectSlab, SelectVec, SelectVecAndFut, SelectVecIndexed, SelectWakers,
+};
+pub use set::StaticSet;
+pub use slab::StaticSlab;
+pub use string::StaticString;
+pub use writer::ByteWriter;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum StaticVecError {
     CapacityExceeded,
 }
 
+/// Error returned when an element does not fit into a full container. Carries the rejected
+/// element, so it is not lost.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CapacityError<T> {
+    element: T,
+}
+
+impl<T> CapacityError<T> {
+    pub const fn new(element: T) -> Self {
+        Self { element }
+    }
+
+    pub fn element(&self) -> &T {
+        &self.element
+    }
+
+    pub fn into_inner(self) -> T {
+        self.element
+    }
+}
+
+impl<T> From<CapacityError<T>> for StaticVecError {
+    fn from(_: CapacityError<T>) -> Self {
+        StaticVecError::CapacityExceeded
+    }
+}
+
+/// Error returned by [`StaticVec::try_extend_from_iter`] when the iterator does not fit.
+#[derive(Debug, Clone)]
+pub struct ExtendError<T, I> {
+    consumed: usize,
+    element: T,
+    rest: I,
+}
+
+impl<T, I: Iterator<Item = T>> ExtendError<T, I> {
+    /// Number of items that were pushed before the vec got full.
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+
+    /// The first item that did not fit.
+    pub fn element(&self) -> &T {
+        &self.element
+    }
+
+    /// Returns the first item that did not fit and the not yet consumed rest of the iterator.
+    pub fn into_inner(self) -> (T, I) {
+        (self.element, self.rest)
+    }
+
+    /// Returns an iterator over all items that did not fit, starting with the rejected one.
+    pub fn into_remaining(self) -> core::iter::Chain<core::iter::Once<T>, I> {
+        core::iter::once(self.element).chain(self.rest)
+    }
+}
+
+impl<T, I> From<ExtendError<T, I>> for StaticVecError {
+    fn from(_: ExtendError<T, I>) -> Self {
+        StaticVecError::CapacityExceeded
+    }
+}
+
 #[derive(Debug)]
 pub struct StaticVec<T, const N: usize> {
     len: usize,
     data: [MaybeUninit<T>; N],
 }
 
-fn extend_array<T, const A: usize, const N: usize>(a: [T; A]) -> [MaybeUninit<T>; N]
-where
-    T: Clone,
-    [(); N]:,
-    [(); N - A]:,
-{
-    let mut ary = MaybeUninit::uninit_array();
-    for (idx, val) in a.into_iter().enumerate() {
-        ary[idx] = MaybeUninit::new(val);
+/// Converts `range` into `start..end` within `0..len`, panicking if it is out of bounds.
+fn bounded_range<R: RangeBounds<usize>>(range: R, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.checked_add(1).expect("range start overflows usize"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.checked_add(1).expect("range end overflows usize"),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end,
+        "range start {} is greater than end {}",
+        start,
+        end
+    );
+    assert!(
+        end <= len,
+        "range end {} is out of bounds of length {}",
+        end,
+        len
+    );
+    start..end
+}
+
+const fn extend_array<T, const A: usize, const N: usize>(a: [T; A]) -> [MaybeUninit<T>; N] {
+    const { assert!(A <= N, "array does not fit into the capacity") };
+    let a = ManuallyDrop::new(a);
+    let mut ary = [const { MaybeUninit::uninit() }; N];
+    //safe as A <= N and the source is never dropped, so the elements are moved
+    unsafe {
+        ptr::copy_nonoverlapping(
+            &a as *const ManuallyDrop<[T; A]> as *const T,
+            ary.as_mut_ptr() as *mut T,
+            A,
+        );
     }
     ary
 }
 
 impl<T, const N: usize> StaticVec<T, N> {
-    pub fn new(len: usize) -> Result<Self, StaticVecError> {
+    pub const fn new() -> Self {
+        Self {
+            data: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+        }
+    }
+
+    /// Creates a vec of `len` elements, where the element at index `i` is `f(i)`.
+    pub fn from_fn<F>(len: usize, mut f: F) -> Result<Self, StaticVecError>
+    where
+        F: FnMut(usize) -> T,
+    {
         if len > N {
             return Err(StaticVecError::CapacityExceeded);
         }
-        Ok(Self {
-            data: MaybeUninit::uninit_array(),
-            len,
-        })
+        let mut ret = Self::new();
+        for i in 0..len {
+            // len is bumped after every write, so a panic in `f` drops only the initialized part
+            ret.data[i].write(f(i));
+            ret.len += 1;
+        }
+        Ok(ret)
+    }
+
+    /// Creates a vec of `len` clones of `value`.
+    pub fn repeat(value: T, len: usize) -> Result<Self, StaticVecError>
+    where
+        T: Clone,
+    {
+        Self::from_fn(len, |_| value.clone())
+    }
+
+    /// Creates a vec of `len` default values.
+    pub fn with_default(len: usize) -> Result<Self, StaticVecError>
+    where
+        T: Default,
+    {
+        Self::from_fn(len, |_| T::default())
     }
 
-    pub fn len(&self) -> usize {
+    /// Creates a vec from raw storage, of which the first `len` elements are in use.
+    ///
+    /// # Safety
+    ///
+    /// `len` must be less than or equal to `N` and the elements `data[..len]` must be
+    /// initialized. Ownership of those elements is transferred to the returned vec, which
+    /// drops them; the elements `data[len..]` are ignored and never dropped.
+    pub const unsafe fn from_raw_parts(data: [MaybeUninit<T>; N], len: usize) -> Self {
+        debug_assert!(len <= N);
+        Self { data, len }
+    }
+
+    pub const fn len(&self) -> usize {
         self.len
     }
 
-    pub fn is_empty(&self) -> bool {
+    pub const fn is_empty(&self) -> bool {
         self.len == 0
     }
 
-    pub fn as_slice(&self) -> &[T] {
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub const fn as_slice(&self) -> &[T] {
         //safe as we ensure that 0..len elements are initialized
-        unsafe { core::mem::transmute::<_, &[T]>(&self.data[..self.len]) }
+        unsafe { slice::from_raw_parts(self.data_ptr(), self.len) }
     }
 
-    pub fn as_mut_slice(&mut self) -> &mut [T] {
+    pub const fn as_mut_slice(&mut self) -> &mut [T] {
         //safe as we ensure that 0..len elements are initialized
-        unsafe { core::mem::transmute::<_, &mut [T]>(&mut self.data[..self.len]) }
+        unsafe { slice::from_raw_parts_mut(self.data_mut_ptr(), self.len) }
     }
 
     pub fn iter(&self) -> slice::Iter<'_, T> {
@@ -74,18 +244,21 @@ impl<T, const N: usize> StaticVec<T, N> {
         }
     }
 
-    fn resize(&mut self, new_len: usize) -> Result<(), StaticVecError> {
-        if new_len > N {
-            return Err(StaticVecError::CapacityExceeded);
-        }
-        self.len = new_len;
-        Ok(())
+    const fn data_ptr(&self) -> *const T {
+        self.data.as_ptr() as *const T
     }
 
-    pub fn push(&mut self, item: T) -> Result<(), StaticVecError> {
+    const fn data_mut_ptr(&mut self) -> *mut T {
+        self.data.as_mut_ptr() as *mut T
+    }
+
+    pub const fn push(&mut self, item: T) -> Result<(), CapacityError<T>> {
         let old_len = self.len();
-        self.resize(old_len + 1)?;
-        self.data[old_len].write(item);
+        if old_len == N {
+            return Err(CapacityError::new(item));
+        }
+        self.data[old_len] = MaybeUninit::new(item);
+        self.len += 1;
         Ok(())
     }
 
@@ -96,16 +269,29 @@ impl<T, const N: usize> StaticVec<T, N> {
         self.try_extend_from_iter_ref(other.iter())
     }
 
+    /// Pushes items from `iter` until it is exhausted or the vec is full.
+    ///
+    /// If the vec runs out of space, the items pushed so far are kept and the error hands back
+    /// the first rejected item together with the rest of the iterator.
     pub fn try_extend_from_iter<I: Iterator<Item = T>>(
         &mut self,
-        iter: I,
-    ) -> Result<(), StaticVecError> {
-        for it in iter {
+        mut iter: I,
+    ) -> Result<(), ExtendError<T, I>> {
+        let mut consumed = 0;
+        while let Some(it) = iter.next() {
             let last_item = self.len();
-            self.resize(last_item + 1)?;
+            if last_item == N {
+                return Err(ExtendError {
+                    consumed,
+                    element: it,
+                    rest: iter,
+                });
+            }
             unsafe {
                 *self.data.get_unchecked_mut(last_item) = MaybeUninit::new(it);
             }
+            self.len += 1;
+            consumed += 1;
         }
         Ok(())
     }
@@ -118,16 +304,58 @@ impl<T, const N: usize> StaticVec<T, N> {
         T: 'a + Clone,
     {
         self.try_extend_from_iter(iter.cloned())
+            .map_err(StaticVecError::from)
+    }
+
+    /// Pushes all items from `iter`, or none of them.
+    ///
+    /// If the vec runs out of space, it is truncated back to its original length and the already
+    /// pushed items are dropped. The same happens if `iter` panics. An iterator with an exact
+    /// `size_hint` that does not fit is rejected before any item is consumed.
+    pub fn try_extend_from_iter_atomic<I: Iterator<Item = T>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), StaticVecError> {
+        if let (lower, Some(upper)) = iter.size_hint() {
+            if lower == upper && lower > N - self.len {
+                return Err(StaticVecError::CapacityExceeded);
+            }
+        }
+
+        let guard = TruncateGuard {
+            len: self.len,
+            vec: self,
+        };
+        guard.vec.try_extend_from_iter(iter)?;
+        core::mem::forget(guard);
+        Ok(())
     }
 
-    pub fn from_array<const A: usize>(value: [T; A]) -> Self
+    /// Clones all items from `other` into the vec, or none of them.
+    pub fn try_extend_from_slice_atomic(&mut self, other: &[T]) -> Result<(), StaticVecError>
     where
         T: Clone,
-        [(); N - A]:,
     {
-        let mut x: Self = extend_array(value).into();
-        x.resize(A).unwrap();
-        x
+        self.try_extend_from_iter_atomic(other.iter().cloned())
+    }
+
+    /// Creates a vec from an array. Fails to compile if `A > N`.
+    ///
+    /// ```
+    /// use simplestaticvec::StaticVec;
+    ///
+    /// const VEC: StaticVec<u8, 4> = StaticVec::from_array([1, 2]);
+    /// assert_eq!(VEC.as_slice(), &[1, 2]);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use simplestaticvec::StaticVec;
+    ///
+    /// let vec = StaticVec::<u8, 2>::from_array([1, 2, 3]);
+    /// ```
+    pub const fn from_array<const A: usize>(value: [T; A]) -> Self {
+        //safe as extend_array initializes the first A elements
+        unsafe { Self::from_raw_parts(extend_array(value), A) }
     }
 
     pub fn remove(&mut self, index: usize) -> T {
@@ -153,6 +381,480 @@ impl<T, const N: usize> StaticVec<T, N> {
             ret
         }
     }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        //safe as the element at old len - 1 is initialized and no longer tracked by len
+        unsafe { Some(self.data.get_unchecked(self.len).assume_init_read()) }
+    }
+
+    /// Inserts `item` at `index`, shifting all elements after it to the right.
+    ///
+    /// Panics if `index > len` or if the vec is full.
+    pub fn insert(&mut self, index: usize, item: T) {
+        if self.try_insert(index, item).is_err() {
+            panic!("insertion exceeds capacity {}", N);
+        }
+    }
+
+    /// Inserts `item` at `index`, shifting all elements after it to the right.
+    ///
+    /// Returns an error carrying `item` if the vec is full. Panics if `index > len`.
+    pub fn try_insert(&mut self, index: usize, item: T) -> Result<(), CapacityError<T>> {
+        let len = self.len;
+
+        assert!(index <= len);
+
+        if len == N {
+            return Err(CapacityError::new(item));
+        }
+
+        unsafe {
+            let ptr = self.data_mut_ptr().add(index);
+            // Shift everything up to make room for the new element.
+            ptr::copy(ptr, ptr.add(1), len - index);
+            ptr::write(ptr, item);
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Shortens the vec to `len` elements, dropping the rest. Does nothing if `len` is greater
+    /// than the current length.
+    pub fn truncate(&mut self, len: usize) {
+        let old_len = self.len;
+        if len >= old_len {
+            return;
+        }
+        // shrink first, so a panicking destructor can never lead to a double drop
+        self.len = len;
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                self.data_mut_ptr().add(len),
+                old_len - len,
+            ));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Removes the element at `index` and replaces it with the last element.
+    ///
+    /// Panics if `index >= len`.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let len = self.len;
+
+        assert!(index < len);
+
+        unsafe {
+            let ptr = self.data_mut_ptr();
+            let ret = ptr::read(ptr.add(index));
+            ptr::copy(ptr.add(len - 1), ptr.add(index), 1);
+            self.len -= 1;
+            ret
+        }
+    }
+
+    /// Moves the elements `at..len` into a new vec, leaving `0..at` in `self`.
+    ///
+    /// Returns an error and leaves `self` unchanged if the tail does not fit into `M`.
+    /// Panics if `at > len`.
+    pub fn split_off<const M: usize>(
+        &mut self,
+        at: usize,
+    ) -> Result<StaticVec<T, M>, StaticVecError> {
+        let len = self.len;
+
+        assert!(at <= len);
+
+        let count = len - at;
+        if count > M {
+            return Err(StaticVecError::CapacityExceeded);
+        }
+
+        let mut other = StaticVec::<T, M>::new();
+        unsafe {
+            ptr::copy_nonoverlapping(self.data_ptr().add(at), other.data_mut_ptr(), count);
+        }
+        self.len = at;
+        other.len = count;
+        Ok(other)
+    }
+
+    /// Moves all elements of `other` to the end of `self`, leaving `other` empty.
+    ///
+    /// Returns an error and leaves both vecs unchanged if the elements do not fit.
+    pub fn append<const M: usize>(
+        &mut self,
+        other: &mut StaticVec<T, M>,
+    ) -> Result<(), StaticVecError> {
+        let len = self.len;
+        let count = other.len;
+        if count > N - len {
+            return Err(StaticVecError::CapacityExceeded);
+        }
+
+        unsafe {
+            ptr::copy_nonoverlapping(other.data_ptr(), self.data_mut_ptr().add(len), count);
+        }
+        other.len = 0;
+        self.len += count;
+        Ok(())
+    }
+
+    /// Removes the elements in `range` and returns them as an iterator.
+    ///
+    /// The elements are removed even if the iterator is not fully consumed. Panics if the range is
+    /// out of bounds.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, N> {
+        let range = bounded_range(range, self.len);
+        Drain::new(self, range)
+    }
+
+    /// Replaces the elements in `range` with the items of `replace_with` and returns the removed
+    /// elements.
+    ///
+    /// If the result would not fit into `N` (or `replace_with` panics), the items taken from
+    /// `replace_with` are dropped, an error is returned and the vec is left unchanged. Panics if
+    /// the range is out of bounds.
+    pub fn splice<R, I>(
+        &mut self,
+        range: R,
+        replace_with: I,
+    ) -> Result<IntoIter<T, N>, StaticVecError>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        let len = self.len;
+        let range = bounded_range(range, len);
+        let removed_len = range.end - range.start;
+        let tail_len = len - range.end;
+        let available = N - (len - removed_len);
+
+        let iter = replace_with.into_iter();
+        if let (lower, Some(upper)) = iter.size_hint() {
+            if lower == upper && lower > available {
+                return Err(StaticVecError::CapacityExceeded);
+            }
+        }
+
+        // the removed elements are moved out and the tail to the end of the buffer, so the
+        // replacement is written straight into the gap between them
+        let mut removed = StaticVec::<T, N>::new();
+        unsafe {
+            let ptr = self.data_mut_ptr();
+            ptr::copy_nonoverlapping(ptr.add(range.start), removed.data_mut_ptr(), removed_len);
+            removed.len = removed_len;
+            ptr::copy(ptr.add(range.end), ptr.add(N - tail_len), tail_len);
+        }
+        self.len = range.start;
+        let mut guard = SpliceGuard {
+            tail: MoveTailGuard {
+                vec: self,
+                tail_start: N - tail_len,
+                tail_len,
+            },
+            removed: &mut removed,
+            start: range.start,
+            restore: true,
+        };
+
+        for it in iter {
+            let vec = &mut *guard.tail.vec;
+            if vec.len == range.start + available {
+                // dropped here rather than by the guard, so the removed elements still go back
+                // if one of the destructors panics
+                vec.truncate(range.start);
+                return Err(StaticVecError::CapacityExceeded);
+            }
+            //safe as the gap before the tail has room for `available` elements
+            unsafe { vec.data_mut_ptr().add(vec.len).write(it) };
+            vec.len += 1;
+        }
+        guard.restore = false;
+        drop(guard);
+        Ok(removed.into_iter())
+    }
+
+    /// Replaces the elements in `range` with clones of `other`, dropping the removed elements.
+    ///
+    /// Returns an error and leaves the vec unchanged if the result would not fit into `N`.
+    /// Panics if the range is out of bounds.
+    pub fn try_replace_range<R>(&mut self, range: R, other: &[T]) -> Result<(), StaticVecError>
+    where
+        R: RangeBounds<usize>,
+        T: Clone,
+    {
+        let len = self.len;
+        let range = bounded_range(range, len);
+        if len - (range.end - range.start) + other.len() > N {
+            return Err(StaticVecError::CapacityExceeded);
+        }
+
+        // the vec ends before the range while it is replaced, the guard moves the tail back
+        // behind the clones even if a destructor or `clone` panics
+        self.len = range.start;
+        let mut guard = MoveTailGuard {
+            vec: self,
+            tail_start: range.end,
+            tail_len: len - range.end,
+        };
+        unsafe {
+            let ptr = guard.vec.data_mut_ptr();
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                ptr.add(range.start),
+                range.end - range.start,
+            ));
+            ptr::copy(
+                ptr.add(range.end),
+                ptr.add(range.start + other.len()),
+                guard.tail_len,
+            );
+        }
+        guard.tail_start = range.start + other.len();
+        for it in other {
+            //safe as the gap before the tail has room for all of `other`
+            unsafe {
+                guard
+                    .vec
+                    .data_mut_ptr()
+                    .add(guard.vec.len)
+                    .write(it.clone())
+            };
+            guard.vec.len += 1;
+        }
+        Ok(())
+    }
+
+    /// Removes the elements in `range` for which `filter` returns `true` and yields them.
+    ///
+    /// Elements not visited because the iterator is dropped early are kept. Panics if the range
+    /// is out of bounds.
+    pub fn extract_if<F, R>(&mut self, range: R, filter: F) -> ExtractIf<'_, T, F, N>
+    where
+        F: FnMut(&mut T) -> bool,
+        R: RangeBounds<usize>,
+    {
+        let range = bounded_range(range, self.len);
+        ExtractIf::new(self, range, filter)
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, preserving their order.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|it| f(it))
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, preserving their order.
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let original_len = self.len;
+        // the vec is kept empty while elements are moved around, the guard restores a valid
+        // state even if `f` or a destructor panics
+        self.len = 0;
+        let mut guard = RetainGuard {
+            vec: self,
+            processed: 0,
+            deleted: 0,
+            original_len,
+        };
+
+        while guard.processed < original_len {
+            unsafe {
+                let ptr = guard.vec.data_mut_ptr();
+                let cur = ptr.add(guard.processed);
+                if !f(&mut *cur) {
+                    guard.processed += 1;
+                    guard.deleted += 1;
+                    ptr::drop_in_place(cur);
+                } else {
+                    if guard.deleted > 0 {
+                        ptr::copy_nonoverlapping(cur, cur.sub(guard.deleted), 1);
+                    }
+                    guard.processed += 1;
+                }
+            }
+        }
+    }
+
+    /// Removes consecutive repeated elements.
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b)
+    }
+
+    /// Removes consecutive elements that map to the same key.
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut T) -> K,
+        K: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+
+    /// Removes consecutive elements for which `same_bucket(current, previous)` returns `true`.
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let len = self.len;
+        if len <= 1 {
+            return;
+        }
+
+        self.len = 0;
+        let mut guard = DedupGuard {
+            vec: self,
+            read: 1,
+            write: 1,
+            original_len: len,
+        };
+
+        while guard.read < len {
+            unsafe {
+                let ptr = guard.vec.data_mut_ptr();
+                let read_ptr = ptr.add(guard.read);
+                let prev_ptr = ptr.add(guard.write - 1);
+                if same_bucket(&mut *read_ptr, &mut *prev_ptr) {
+                    guard.read += 1;
+                    ptr::drop_in_place(read_ptr);
+                } else {
+                    ptr::copy(read_ptr, ptr.add(guard.write), 1);
+                    guard.write += 1;
+                    guard.read += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Closes the gap left by removed elements and restores the length, on success or unwind.
+struct RetainGuard<'a, T, const N: usize> {
+    vec: &'a mut StaticVec<T, N>,
+    processed: usize,
+    deleted: usize,
+    original_len: usize,
+}
+
+impl<'a, T, const N: usize> Drop for RetainGuard<'a, T, N> {
+    fn drop(&mut self) {
+        if self.deleted > 0 {
+            unsafe {
+                let ptr = self.vec.data_mut_ptr();
+                ptr::copy(
+                    ptr.add(self.processed),
+                    ptr.add(self.processed - self.deleted),
+                    self.original_len - self.processed,
+                );
+            }
+        }
+        self.vec.len = self.original_len - self.deleted;
+    }
+}
+
+/// Closes the gap between the written and the not yet read elements, on success or unwind.
+struct DedupGuard<'a, T, const N: usize> {
+    vec: &'a mut StaticVec<T, N>,
+    read: usize,
+    write: usize,
+    original_len: usize,
+}
+
+impl<'a, T, const N: usize> Drop for DedupGuard<'a, T, N> {
+    fn drop(&mut self) {
+        let remaining = self.original_len - self.read;
+        unsafe {
+            let ptr = self.vec.data_mut_ptr();
+            ptr::copy(ptr.add(self.read), ptr.add(self.write), remaining);
+        }
+        self.vec.len = self.write + remaining;
+    }
+}
+
+/// Moves the tail back behind the elements of the vec and restores the length, on success or
+/// unwind.
+struct MoveTailGuard<'a, T, const N: usize> {
+    vec: &'a mut StaticVec<T, N>,
+    tail_start: usize,
+    tail_len: usize,
+}
+
+impl<'a, T, const N: usize> Drop for MoveTailGuard<'a, T, N> {
+    fn drop(&mut self) {
+        let len = self.vec.len;
+        unsafe {
+            let ptr = self.vec.data_mut_ptr();
+            ptr::copy(ptr.add(self.tail_start), ptr.add(len), self.tail_len);
+        }
+        self.vec.len = len + self.tail_len;
+    }
+}
+
+/// Puts the removed elements back in place of the replacement written by [`StaticVec::splice`]
+/// unless it succeeded, then moves the tail back, on success, error or unwind.
+struct SpliceGuard<'a, T, const N: usize> {
+    // dropped after the removed elements are back, even if dropping the replacement panics
+    tail: MoveTailGuard<'a, T, N>,
+    removed: &'a mut StaticVec<T, N>,
+    start: usize,
+    restore: bool,
+}
+
+impl<'a, T, const N: usize> Drop for SpliceGuard<'a, T, N> {
+    fn drop(&mut self) {
+        if !self.restore {
+            return;
+        }
+        let vec = &mut *self.tail.vec;
+        vec.truncate(self.start);
+        let removed_len = self.removed.len;
+        self.removed.len = 0;
+        unsafe {
+            ptr::copy_nonoverlapping(
+                self.removed.data_ptr(),
+                vec.data_mut_ptr().add(self.start),
+                removed_len,
+            );
+        }
+        vec.len += removed_len;
+    }
+}
+
+/// Truncates the vec back to `len` when dropped, unless forgotten.
+struct TruncateGuard<'a, T, const N: usize> {
+    vec: &'a mut StaticVec<T, N>,
+    len: usize,
+}
+
+impl<'a, T, const N: usize> Drop for TruncateGuard<'a, T, N> {
+    fn drop(&mut self) {
+        self.vec.truncate(self.len);
+    }
+}
+
+impl<T, const N: usize> Drop for StaticVec<T, N> {
+    fn drop(&mut self) {
+        let len = self.len;
+        // reset len first, so a panicking destructor can never lead to a double drop
+        self.len = 0;
+        //safe as we ensure that 0..len elements are initialized,
+        //drop_in_place on a slice keeps dropping the remaining elements if one of them panics
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.data_mut_ptr(), len));
+        }
+    }
 }
 
 impl<T, const N: usize> Clone for StaticVec<T, N>
@@ -161,7 +863,7 @@ where
 {
     fn clone(&self) -> Self {
         let src = self.as_slice();
-        let mut data = MaybeUninit::uninit_array();
+        let mut data = [const { MaybeUninit::uninit() }; N];
         for i in 0..src.len() {
             data[i] = MaybeUninit::new(src[i].clone());
         }
@@ -194,12 +896,29 @@ impl<'a, T, const N: usize> IntoIterator for &'a StaticVec<T, N> {
     }
 }
 
+impl<'a, T, const N: usize> IntoIterator for &'a mut StaticVec<T, N> {
+    type Item = &'a mut T;
+
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, const N: usize> IntoIterator for StaticVec<T, N> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
 impl<T, const N: usize> Default for StaticVec<T, N> {
     fn default() -> Self {
-        Self {
-            len: 0,
-            data: MaybeUninit::uninit_array(),
-        }
+        Self::new()
     }
 }
 
@@ -221,15 +940,6 @@ impl<T, const N: usize> From<[T; N]> for StaticVec<T, N> {
     }
 }
 
-impl<T, const N: usize> From<[MaybeUninit<T>; N]> for StaticVec<T, N> {
-    fn from(value: [MaybeUninit<T>; N]) -> Self {
-        Self {
-            data: value.map(|x| x),
-            len: N,
-        }
-    }
-}
-
 impl<T, const N: usize> core::ops::Deref for StaticVec<T, N> {
     type Target = [T];
 
@@ -252,64 +962,276 @@ impl<T, const N: usize> core::ops::Index<usize> for StaticVec<T, N> {
     }
 }
 
-pub struct SelectVec<'a, T, const N: usize>(pub &'a mut StaticVec<T, N>);
-impl<'a, T, const N: usize> core::future::Future for SelectVec<'a, T, N>
-where
-    T: core::future::Future + core::marker::Unpin,
-{
-    type Output = T::Output;
-
-    fn poll(
-        self: core::pin::Pin<&mut Self>,
-        cx: &mut core::task::Context<'_>,
-    ) -> core::task::Poll<Self::Output> {
-        let self_mut = self.get_mut();
-        for i in 0..self_mut.0.len() {
-            let fut = unsafe { self_mut.0.get_unchecked_mut(i) };
-            let pin = core::pin::pin!(fut);
-            match core::future::Future::poll(pin, cx) {
-                core::task::Poll::Ready(x) => {
-                    self_mut.0.remove(i);
-                    return core::task::Poll::Ready(x);
-                }
-                core::task::Poll::Pending => {}
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
 
-        core::task::Poll::Pending
+    use super::*;
+    use crate::test_util::{counters, keys, panics, DropCounter};
+
+    #[test]
+    fn from_array_moves_elements() {
+        let drops = Cell::new(0);
+        let vec =
+            StaticVec::<_, 4>::from_array([DropCounter::new(&drops), DropCounter::new(&drops)]);
+        assert_eq!((vec.len(), drops.get()), (2, 0));
+        let clone = vec.clone();
+        assert_eq!(clone.len(), 2);
+        drop(vec);
+        drop(clone);
+        assert_eq!(drops.get(), 4);
+
+        assert_eq!(
+            StaticVec::<u8, 3>::from_array([1, 2, 3]).as_slice(),
+            &[1, 2, 3]
+        );
+        assert_eq!(
+            StaticVec::from([1, 2, 3]),
+            StaticVec::<u8, 3>::from_array([1, 2, 3])
+        );
+        assert!(StaticVec::<u8, 0>::from_array([]).is_empty());
     }
-}
 
-pub struct SelectVecAndFut<'a, T, F, const N: usize>(pub &'a mut StaticVec<T, N>, pub F);
-impl<'a, T, F, const N: usize> core::future::Future for SelectVecAndFut<'a, T, F, N>
-where
-    T: core::future::Future + core::marker::Unpin,
-    F: core::future::Future + core::marker::Unpin,
-{
-    type Output = Either<F::Output, T::Output>;
-
-    fn poll(
-        self: core::pin::Pin<&mut Self>,
-        cx: &mut core::task::Context<'_>,
-    ) -> core::task::Poll<Self::Output> {
-        let self_mut = self.get_mut();
-        let pin = core::pin::pin!(&mut self_mut.1);
-        match core::future::Future::poll(pin, cx) {
-            core::task::Poll::Ready(x) => return core::task::Poll::Ready(either::Left(x)),
-            core::task::Poll::Pending => {}
+    #[cfg(feature = "nightly")]
+    #[test]
+    fn iterators_are_trusted_len() {
+        fn trusted_len<I: core::iter::TrustedLen>(_: I) {}
+
+        let mut vec = StaticVec::<u8, 4>::from_array([1, 2, 3]);
+        trusted_len(vec.drain(..1));
+        trusted_len(vec.into_iter());
+    }
+
+    #[test]
+    fn const_accessors() {
+        static EMPTY: StaticVec<u8, 4> = StaticVec::new();
+        static VEC: StaticVec<u8, 4> = {
+            let mut vec = StaticVec::from_array([3, 2]);
+            vec.as_mut_slice()[0] = 1;
+            vec
         };
-        for i in 0..self_mut.0.len() {
-            let fut = unsafe { self_mut.0.get_unchecked_mut(i) };
-            let pin = core::pin::pin!(fut);
-            match core::future::Future::poll(pin, cx) {
-                core::task::Poll::Ready(x) => {
-                    self_mut.0.remove(i);
-                    return core::task::Poll::Ready(either::Right(x));
-                }
-                core::task::Poll::Pending => {}
-            }
-        }
+        static LEN: usize = VEC.len();
+        static FIRST: u8 = VEC.as_slice()[0];
+        const RAW: StaticVec<u8, 2> = {
+            let data = [MaybeUninit::new(7), MaybeUninit::uninit()];
+            //safe as the first element is initialized
+            unsafe { StaticVec::from_raw_parts(data, 1) }
+        };
+        assert!(EMPTY.is_empty() && !VEC.is_empty());
+        assert_eq!((LEN, FIRST, VEC.capacity()), (2, 1, 4));
+        assert_eq!(VEC.as_slice(), &[1, 2]);
+        assert_eq!(RAW.as_slice(), &[7]);
+        assert_eq!(CapacityError::new(5).into_inner(), 5);
+    }
+
+    #[test]
+    fn sized_constructors() {
+        let vec = StaticVec::<usize, 4>::from_fn(3, |i| i * 2).unwrap();
+        assert_eq!(vec.as_slice(), &[0, 2, 4]);
+        assert!(StaticVec::<usize, 4>::from_fn(5, |i| i).is_err());
+        assert_eq!(
+            StaticVec::<u8, 4>::repeat(7, 2).unwrap().as_slice(),
+            &[7, 7]
+        );
+        assert_eq!(
+            StaticVec::<u8, 4>::with_default(4).unwrap().as_slice(),
+            &[0; 4]
+        );
+        assert!(StaticVec::<u8, 4>::with_default(5).is_err());
+    }
+
+    #[test]
+    fn from_fn_drops_built_elements_after_panic() {
+        let drops = Cell::new(0);
+        assert!(panics(|| {
+            let _ = StaticVec::<_, 4>::from_fn(4, |i| {
+                assert_ne!(i, 2);
+                DropCounter::new(&drops)
+            });
+        }));
+        assert_eq!(drops.get(), 2);
+    }
+
+    #[test]
+    fn const_push() {
+        const VEC: StaticVec<u8, 2> = {
+            let mut vec = StaticVec::new();
+            assert!(vec.push(1).is_ok());
+            assert!(vec.push(2).is_ok());
+            assert!(vec.push(3).is_err());
+            vec
+        };
+        assert_eq!(VEC.as_slice(), &[1, 2]);
+    }
+
+    #[test]
+    fn try_replace_range_in_place() {
+        let mut vec = StaticVec::<u8, 6>::new();
+        vec.try_extend_from_slice_atomic(&[0, 1, 2, 3]).unwrap();
+        vec.try_replace_range(1..2, &[7, 8, 9]).unwrap();
+        assert_eq!(vec.as_slice(), &[0, 7, 8, 9, 2, 3]);
+        vec.try_replace_range(..4, &[5]).unwrap();
+        assert_eq!(vec.as_slice(), &[5, 2, 3]);
+        assert!(vec.try_replace_range(1..1, &[0; 4]).is_err());
+        assert_eq!(vec.as_slice(), &[5, 2, 3]);
+    }
+
+    #[test]
+    fn try_replace_range_keeps_tail_after_panic() {
+        let drops = Cell::new(0);
+        let mut vec = StaticVec::<_, 4>::new();
+        vec.push(DropCounter::new(&drops)).unwrap();
+        vec.push(DropCounter::panicking(&drops)).unwrap();
+        vec.push(DropCounter::new(&drops)).unwrap();
+        let other = [DropCounter::new(&drops)];
+        assert!(panics(|| vec.try_replace_range(1..2, &other).unwrap()));
+        assert_eq!(drops.get(), 1);
+        assert_eq!(vec.len(), 2);
+        drop(vec);
+        assert_eq!(drops.get(), 3);
+    }
+
+    #[test]
+    fn splice_restores_vec() {
+        let mut vec = StaticVec::<u8, 4>::new();
+        vec.try_extend_from_slice_atomic(&[0, 1, 2]).unwrap();
+        let removed = vec.splice(1..2, [7, 8]).unwrap();
+        assert_eq!(removed.as_slice(), &[1]);
+        assert_eq!(vec.as_slice(), &[0, 7, 8, 2]);
+        // the size hint does not tell the length, so the items are written before the error
+        assert!(vec.splice(.., (0..9).filter(|_| true)).is_err());
+        assert_eq!(vec.as_slice(), &[0, 7, 8, 2]);
+        assert!(panics(|| {
+            let _ = vec.splice(1..3, (0..2).map(|it| if it == 1 { panic!() } else { it }));
+        }));
+        assert_eq!(vec.as_slice(), &[0, 7, 8, 2]);
+    }
+
+    #[test]
+    fn retain_keeps_order_after_panic() {
+        let drops = Cell::new(0);
+        let mut vec = counters(&drops, 6);
+        vec.retain(|it| it.0 % 2 == 0);
+        assert_eq!(keys(&vec).as_slice(), &[0, 2, 4]);
+        assert_eq!(drops.get(), 3);
+
+        let mut vec = counters(&drops, 6);
+        assert!(panics(|| vec.retain(|it| {
+            assert_ne!(it.0, 4);
+            it.0 != 1
+        })));
+        // the element the filter panicked on and the ones after it are kept
+        assert_eq!(keys(&vec).as_slice(), &[0, 2, 3, 4, 5]);
+        assert_eq!(drops.get(), 4);
+        drop(vec);
+        assert_eq!(drops.get(), 9);
+    }
+
+    #[test]
+    fn retain_keeps_order_after_drop_panic() {
+        let drops = Cell::new(0);
+        let mut vec = counters(&drops, 5);
+        vec.as_mut_slice()[1].1 = DropCounter::panicking(&drops);
+        assert_eq!(drops.get(), 1);
+        assert!(panics(|| vec.retain(|it| it.0 != 1 && it.0 != 3)));
+        assert_eq!(keys(&vec).as_slice(), &[0, 2, 3, 4]);
+        assert_eq!(drops.get(), 2);
+        drop(vec);
+        assert_eq!(drops.get(), 6);
+    }
+
+    #[test]
+    fn dedup_keeps_order_after_panic() {
+        let drops = Cell::new(0);
+        let mut vec = counters(&drops, 6);
+        vec.dedup_by(|a, b| a.0 / 2 == b.0 / 2);
+        assert_eq!(keys(&vec).as_slice(), &[0, 2, 4]);
+        assert_eq!(drops.get(), 3);
+
+        let mut vec = counters(&drops, 6);
+        let same_half = |a: &mut (u8, DropCounter), b: &mut (u8, DropCounter)| a.0 / 2 == b.0 / 2;
+        assert!(panics(|| vec.dedup_by(|a, b| {
+            assert_ne!(a.0, 3);
+            same_half(a, b)
+        })));
+        // the element the predicate panicked on and the ones after it are kept
+        assert_eq!(keys(&vec).as_slice(), &[0, 2, 3, 4, 5]);
+        assert_eq!(drops.get(), 4);
+
+        vec.as_mut_slice()[2].1 = DropCounter::panicking(&drops);
+        assert_eq!(drops.get(), 5);
+        assert!(panics(|| vec.dedup_by(same_half)));
+        assert_eq!(keys(&vec).as_slice(), &[0, 2, 4, 5]);
+        assert_eq!(drops.get(), 6);
+        drop(vec);
+        assert_eq!(drops.get(), 10);
+    }
+
+    #[test]
+    fn extend_error_hands_back_remaining() {
+        let mut vec = StaticVec::<u8, 4>::from_array([0, 1]);
+        let err = vec.try_extend_from_iter(2..7).unwrap_err();
+        assert_eq!(err.consumed(), 2);
+        assert_eq!(*err.element(), 4);
+        assert!(err.into_remaining().eq(4..7));
+        assert_eq!(vec.as_slice(), &[0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn failed_atomic_extend_keeps_vec() {
+        let drops = Cell::new(0);
+        let mut vec = counters(&drops, 6);
+        // the filter hides the length, so the items are pushed until the vec is full
+        let iter = (6..9)
+            .map(|i| (i, DropCounter::new(&drops)))
+            .filter(|_| true);
+        assert!(vec.try_extend_from_iter_atomic(iter).is_err());
+        assert_eq!(keys(&vec).as_slice(), &[0, 1, 2, 3, 4, 5]);
+        assert_eq!(drops.get(), 3);
+
+        let mut made = 0;
+        let iter = (6..9).map(|i| {
+            made += 1;
+            (i, DropCounter::new(&drops))
+        });
+        assert!(vec.try_extend_from_iter_atomic(iter).is_err());
+        assert_eq!(made, 0);
+        assert_eq!(vec.len(), 6);
+    }
+
+    #[test]
+    fn atomic_extend_truncates_after_panic() {
+        let drops = Cell::new(0);
+        let mut vec = counters(&drops, 2);
+        let iter = (0..4).map(|i| (i, DropCounter::new(&drops)));
+        assert!(vec
+            .try_extend_from_iter_atomic(iter.filter(|_| true))
+            .is_ok());
+        assert_eq!(vec.len(), 6);
+
+        let iter = (6..10).map(|i| (i, DropCounter::new(&drops)));
+        assert!(vec
+            .try_extend_from_iter_atomic(iter.filter(|_| true))
+            .is_err());
+        assert_eq!(vec.len(), 6);
+        assert_eq!(drops.get(), 3);
+
+        let iter = (6..8).map(|i| (i, DropCounter::new(&drops)));
+        let iter = iter.chain(core::iter::from_fn(|| panic!()));
+        assert!(panics(|| {
+            let _ = vec.try_extend_from_iter_atomic(iter);
+        }));
+        assert_eq!(keys(&vec).as_slice(), &[0, 1, 0, 1, 2, 3]);
+        assert_eq!(drops.get(), 5);
+    }
 
-        core::task::Poll::Pending
+    #[test]
+    fn drop_continues_after_panic() {
+        let drops = Cell::new(0);
+        assert!(panics(|| {
+            let mut vec = counters(&drops, 4);
+            vec.as_mut_slice()[1].1 = DropCounter::panicking(&drops);
+        }));
+        assert_eq!(drops.get(), 5);
     }
 }
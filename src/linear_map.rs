@@ -0,0 +1,240 @@
+//! Fixed-capacity unsorted map, for keys that are only `Eq`.
+
+use core::borrow::Borrow;
+use core::fmt;
+
+use crate::map::{Iter, IterMut};
+use crate::{CapacityError, IntoIter as VecIntoIter, StaticVec};
+
+/// Fixed-capacity map, keeping its entries unsorted in a [`StaticVec`].
+///
+/// Lookups are O(n) and only need `K: Eq`. Removal swaps the last entry into the gap, so the
+/// iteration order is not stable.
+pub struct LinearMap<K, V, const N: usize> {
+    data: StaticVec<(K, V), N>,
+}
+
+impl<K, V, const N: usize> LinearMap<K, V, N> {
+    pub const fn new() -> Self {
+        Self {
+            data: StaticVec::new(),
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    pub fn as_slice(&self) -> &[(K, V)] {
+        &self.data
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(&self.data)
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut::new(&mut self.data)
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator + '_ {
+        self.data.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator + '_ {
+        self.data.iter().map(|(_, v)| v)
+    }
+
+    pub fn values_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut V> + ExactSizeIterator + '_ {
+        self.data.iter_mut().map(|(_, v)| v)
+    }
+
+    /// Keeps only the entries for which `f` returns `true`.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.data.retain_mut(|(k, v)| f(k, v))
+    }
+}
+
+impl<K: Eq, V, const N: usize> LinearMap<K, V, N> {
+    fn position<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.data.iter().position(|(k, _)| k.borrow() == key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.position(key).is_some()
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let idx = self.position(key)?;
+        Some(&self.data[idx].1)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let idx = self.position(key)?;
+        Some(&mut self.data.as_mut_slice()[idx].1)
+    }
+
+    /// Inserts `value` under `key`, returning the previous value of `key`.
+    ///
+    /// If the key is new and the map is full, the entry is handed back in the error.
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, CapacityError<(K, V)>> {
+        match self.position(&key) {
+            Some(idx) => Ok(Some(core::mem::replace(
+                &mut self.data.as_mut_slice()[idx].1,
+                value,
+            ))),
+            None => {
+                self.data.push((key, value))?;
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let idx = self.position(key)?;
+        Some(self.data.swap_remove(idx))
+    }
+}
+
+impl<K, V, const N: usize> Default for LinearMap<K, V, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Clone, V: Clone, const N: usize> Clone for LinearMap<K, V, N> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, const N: usize> fmt::Debug for LinearMap<K, V, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Eq, V: PartialEq, const N: usize> PartialEq for LinearMap<K, V, N> {
+    /// Maps are equal if they contain the same entries, in any order.
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K: Eq, V: Eq, const N: usize> Eq for LinearMap<K, V, N> {}
+
+impl<K, V, Q, const N: usize> core::ops::Index<&Q> for LinearMap<K, V, N>
+where
+    K: Borrow<Q> + Eq,
+    Q: Eq + ?Sized,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &Self::Output {
+        self.get(key).expect("key not found")
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a LinearMap<K, V, N> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a mut LinearMap<K, V, N> {
+    type Item = (&'a K, &'a mut V);
+
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, const N: usize> IntoIterator for LinearMap<K, V, N> {
+    type Item = (K, V);
+
+    type IntoIter = VecIntoIter<(K, V), N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_replace_and_remove() {
+        let mut map = LinearMap::<&str, u8, 3>::new();
+        assert_eq!(map.insert("b", 1).ok(), Some(None));
+        assert_eq!(map.insert("a", 2).ok(), Some(None));
+        assert_eq!(map.insert("c", 3).ok(), Some(None));
+        // entries keep their insertion order
+        assert!(map.keys().eq(&["b", "a", "c"]));
+        assert_eq!(map.insert("a", 4).ok(), Some(Some(2)));
+        assert_eq!(map.insert("d", 5).unwrap_err().into_inner(), ("d", 5));
+
+        // the last entry moves into the gap
+        assert_eq!(map.remove("b"), Some(1));
+        assert!(map.keys().eq(&["c", "a"]));
+        *map.get_mut("c").unwrap() += 1;
+        assert_eq!((map.get("c"), map.get("b")), (Some(&4), None));
+        map.retain(|_, v| *v > 3);
+        assert_eq!(map.as_slice(), &[("c", 4), ("a", 4)]);
+        assert_eq!(map.remove_entry("a"), Some(("a", 4)));
+        assert!(!map.contains_key("a"));
+    }
+}
@@ -0,0 +1,518 @@
+//! Fixed-capacity least recently used cache.
+
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::iter::FusedIterator;
+use core::{fmt, mem};
+
+use crate::hash_map::{FnvBuildHasher, IndexTable};
+use crate::{CapacityError, StaticVec};
+
+/// Marks the end of the recency list.
+const NIL: usize = usize::MAX;
+
+#[derive(Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // neighbours in the recency list, towards the most and the least recently used entry
+    prev: usize,
+    next: usize,
+}
+
+/// Outcome of [`StaticLru::put`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Put<K, V> {
+    /// The key was new, and there was room for it.
+    Inserted,
+    /// The key was cached, and this was its previous value.
+    Replaced(V),
+    /// The key was new, and this least recently used entry was evicted to make room. With a
+    /// capacity of zero, it is the new entry itself.
+    Evicted(K, V),
+}
+
+/// Fixed-capacity cache evicting the least recently used entry.
+///
+/// The entries are stored densely in a [`StaticVec`], found through a hash index and linked
+/// by their indices into a recency list, so lookups, promotions and evictions are O(1).
+pub struct StaticLru<K, V, const N: usize, S = FnvBuildHasher> {
+    entries: StaticVec<Node<K, V>, N>,
+    table: IndexTable<N>,
+    // most recently used entry
+    head: usize,
+    // least recently used entry
+    tail: usize,
+    cap: usize,
+    hasher: S,
+}
+
+impl<K, V, const N: usize, S: Default> StaticLru<K, V, N, S> {
+    pub fn new() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, const N: usize, S> StaticLru<K, V, N, S> {
+    pub const fn with_hasher(hasher: S) -> Self {
+        Self {
+            entries: StaticVec::new(),
+            table: IndexTable::new(),
+            head: NIL,
+            tail: NIL,
+            cap: N,
+            hasher,
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the current capacity, which is `N` unless lowered by [`Self::resize_down`].
+    pub const fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.table.clear();
+        self.head = NIL;
+        self.tail = NIL;
+    }
+
+    /// Returns the least recently used entry, without promoting it.
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        let node = self.entries.get(self.tail)?;
+        Some((&node.key, &node.value))
+    }
+
+    /// Returns an iterator over the entries, from the most to the least recently used one.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            entries: &self.entries,
+            next: self.head,
+            len: self.len(),
+        }
+    }
+
+    /// Takes the entry at `idx` out of the recency list.
+    fn unlink(&mut self, idx: usize) {
+        let (prev, next) = (self.entries[idx].prev, self.entries[idx].next);
+        let entries = self.entries.as_mut_slice();
+        match prev {
+            NIL => self.head = next,
+            prev => entries[prev].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => entries[next].prev = prev,
+        }
+    }
+
+    /// Puts the unlinked entry at `idx` to the front of the recency list.
+    fn link_front(&mut self, idx: usize) {
+        let head = self.head;
+        let entries = self.entries.as_mut_slice();
+        entries[idx].prev = NIL;
+        entries[idx].next = head;
+        match head {
+            NIL => self.tail = idx,
+            head => entries[head].prev = idx,
+        }
+        self.head = idx;
+    }
+
+    fn promote(&mut self, idx: usize) {
+        if self.head != idx {
+            self.unlink(idx);
+            self.link_front(idx);
+        }
+    }
+}
+
+impl<K, V, const N: usize, S> StaticLru<K, V, N, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn hash<Q: Hash + ?Sized>(&self, key: &Q) -> usize {
+        self.hasher.hash_one(key) as usize
+    }
+
+    /// Returns the position of the slot of `key` within the index table.
+    fn find<Q>(&self, hash: usize, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.table
+            .find(hash, |idx| self.entries[idx].key.borrow() == key)
+    }
+
+    fn find_index<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let pos = self.find(self.hash(key), key)?;
+        Some(self.table.idx(pos))
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find_index(key).is_some()
+    }
+
+    /// Returns the value of `key` and marks it as the most recently used entry.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find_index(key)?;
+        self.promote(idx);
+        Some(&self.entries[idx].value)
+    }
+
+    /// Returns the value of `key` mutably and marks it as the most recently used entry.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find_index(key)?;
+        self.promote(idx);
+        Some(&mut self.entries.as_mut_slice()[idx].value)
+    }
+
+    /// Returns the value of `key`, without promoting it.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find_index(key)?;
+        Some(&self.entries[idx].value)
+    }
+
+    /// Inserts `value` under `key` as the most recently used entry.
+    ///
+    /// If `key` is already cached, its value is replaced and returned. Otherwise, if the cache
+    /// is full, the least recently used entry is evicted and returned.
+    pub fn put(&mut self, key: K, value: V) -> Put<K, V> {
+        let hash = self.hash(&key);
+        if let Some(pos) = self.find(hash, &key) {
+            let idx = self.table.idx(pos);
+            self.promote(idx);
+            let old = mem::replace(&mut self.entries.as_mut_slice()[idx].value, value);
+            return Put::Replaced(old);
+        }
+        if self.cap == 0 {
+            return Put::Evicted(key, value);
+        }
+        let evicted = if self.len() == self.cap {
+            self.pop_lru()
+        } else {
+            None
+        };
+        self.insert_new(hash, key, value);
+        match evicted {
+            Some((key, value)) => Put::Evicted(key, value),
+            None => Put::Inserted,
+        }
+    }
+
+    /// Inserts `value` under `key` as the most recently used entry, returning the previous value
+    /// of `key`.
+    ///
+    /// If the key is new and the cache is full, nothing is evicted and the entry is handed back
+    /// in the error.
+    pub fn try_put(&mut self, key: K, value: V) -> Result<Option<V>, CapacityError<(K, V)>> {
+        let hash = self.hash(&key);
+        if let Some(pos) = self.find(hash, &key) {
+            let idx = self.table.idx(pos);
+            self.promote(idx);
+            let old = mem::replace(&mut self.entries.as_mut_slice()[idx].value, value);
+            return Ok(Some(old));
+        }
+        if self.len() == self.cap {
+            return Err(CapacityError::new((key, value)));
+        }
+        self.insert_new(hash, key, value);
+        Ok(None)
+    }
+
+    /// Stores a new entry in front of the recency list. The cache must not be full.
+    fn insert_new(&mut self, hash: usize, key: K, value: V) {
+        let idx = self.entries.len();
+        let node = Node {
+            key,
+            value,
+            prev: NIL,
+            next: NIL,
+        };
+        //cannot fail, as the cache is not full
+        let _ = self.entries.push(node);
+        self.table.insert(hash, idx);
+        self.link_front(idx);
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let pos = self.find(self.hash(key), key)?;
+        let (_, value) = self.remove_at(pos);
+        Some(value)
+    }
+
+    /// Removes the least recently used entry and returns it.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        if self.tail == NIL {
+            return None;
+        }
+        let idx = self.tail;
+        let hash = self.hash(&self.entries[idx].key);
+        //cannot fail, as every entry is stored in the table
+        let pos = self.table.find(hash, |it| it == idx).unwrap();
+        Some(self.remove_at(pos))
+    }
+
+    /// Lowers the capacity to `capacity`, dropping the least recently used entries that no
+    /// longer fit. The capacity can be raised again up to `N` the same way.
+    ///
+    /// Panics if `capacity` is greater than `N`.
+    pub fn resize_down(&mut self, capacity: usize) {
+        assert!(capacity <= N, "capacity {} exceeds {}", capacity, N);
+        while self.len() > capacity {
+            self.pop_lru();
+        }
+        self.cap = capacity;
+    }
+
+    /// Removes the entry whose slot is at `pos` within the index table.
+    fn remove_at(&mut self, pos: usize) -> (K, V) {
+        let idx = self.table.idx(pos);
+        self.table.remove(pos);
+        self.unlink(idx);
+
+        let node = self.entries.swap_remove(idx);
+        // the last entry was moved into the gap, so its links have to follow
+        let moved = self.entries.len();
+        if idx != moved {
+            let (prev, next) = (self.entries[idx].prev, self.entries[idx].next);
+            let entries = self.entries.as_mut_slice();
+            match prev {
+                NIL => self.head = idx,
+                prev => entries[prev].next = idx,
+            }
+            match next {
+                NIL => self.tail = idx,
+                next => entries[next].prev = idx,
+            }
+            let hash = self.hash(&self.entries[idx].key);
+            self.table.relink(hash, moved, idx);
+        }
+        (node.key, node.value)
+    }
+}
+
+/// Iterator over the entries of a [`StaticLru`], from the most to the least recently used one.
+pub struct Iter<'a, K, V> {
+    entries: &'a [Node<K, V>],
+    next: usize,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.entries.get(self.next)?;
+        self.next = node.next;
+        self.len -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> Clone for Iter<'a, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries,
+            next: self.next,
+            len: self.len,
+        }
+    }
+}
+
+impl<K, V, const N: usize, S: Default> Default for StaticLru<K, V, N, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Clone, V: Clone, const N: usize, S: Clone> Clone for StaticLru<K, V, N, S> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            table: self.table.clone(),
+            head: self.head,
+            tail: self.tail,
+            cap: self.cap,
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, const N: usize, S> fmt::Debug for StaticLru<K, V, N, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K, V, const N: usize, S> IntoIterator for &'a StaticLru<K, V, N, S> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use super::*;
+    use crate::test_util::{panics, DropCounter};
+
+    fn lru_keys<V, const N: usize>(lru: &StaticLru<u8, V, N>) -> StaticVec<u8, N> {
+        let mut keys = StaticVec::new();
+        for (k, _) in lru.iter() {
+            keys.push(*k).unwrap();
+        }
+        keys
+    }
+
+    #[test]
+    fn put_tells_replaced_from_evicted() {
+        let mut lru = StaticLru::<u32, u32, 2>::new();
+        assert_eq!(lru.put(1, 10), Put::Inserted);
+        assert_eq!(lru.put(2, 20), Put::Inserted);
+        assert_eq!(lru.put(1, 11), Put::Replaced(10));
+        assert_eq!(lru.put(3, 30), Put::Evicted(2, 20));
+        lru.resize_down(0);
+        assert_eq!(lru.put(4, 40), Put::Evicted(4, 40));
+        assert!(lru.is_empty());
+    }
+
+    #[test]
+    fn put_hands_back_replaced_and_evicted_values() {
+        let drops = Cell::new(0);
+        let mut lru = StaticLru::<u8, DropCounter<'_>, 2>::new();
+        assert!(matches!(
+            lru.put(1, DropCounter::new(&drops)),
+            Put::Inserted
+        ));
+        assert!(matches!(
+            lru.put(2, DropCounter::new(&drops)),
+            Put::Inserted
+        ));
+        let replaced = lru.put(1, DropCounter::new(&drops));
+        assert!(matches!(replaced, Put::Replaced(_)));
+        assert_eq!(drops.get(), 0);
+        drop(replaced);
+        assert_eq!(drops.get(), 1);
+        // 2 is the least recently used entry, as 1 was put again
+        assert!(matches!(
+            lru.put(3, DropCounter::new(&drops)),
+            Put::Evicted(2, _)
+        ));
+        assert_eq!(drops.get(), 2);
+        assert_eq!(lru_keys(&lru).as_slice(), &[3, 1]);
+        drop(lru);
+        assert_eq!(drops.get(), 4);
+    }
+
+    #[test]
+    fn get_promotes_and_peek_does_not() {
+        let mut lru = StaticLru::<u8, u8, 3>::new();
+        for i in 0..3 {
+            lru.put(i, i * 10);
+        }
+        assert_eq!(lru_keys(&lru).as_slice(), &[2, 1, 0]);
+        assert_eq!(lru.get(&0), Some(&0));
+        assert_eq!(lru.peek(&1), Some(&10));
+        assert_eq!(lru_keys(&lru).as_slice(), &[0, 2, 1]);
+        assert_eq!(lru.peek_lru(), Some((&1, &10)));
+        *lru.get_mut(&1).unwrap() += 1;
+        assert!(matches!(lru.put(3, 30), Put::Evicted(2, 20)));
+        assert_eq!(lru_keys(&lru).as_slice(), &[3, 1, 0]);
+    }
+
+    #[test]
+    fn pop_lru_takes_oldest_first() {
+        let drops = Cell::new(0);
+        let mut lru = StaticLru::<u8, DropCounter<'_>, 4>::new();
+        for i in 0..4 {
+            lru.put(i, DropCounter::new(&drops));
+        }
+        lru.get(&1);
+        let mut popped = StaticVec::<u8, 4>::new();
+        while let Some((k, _)) = lru.pop_lru() {
+            popped.push(k).unwrap();
+        }
+        assert_eq!(popped.as_slice(), &[0, 2, 3, 1]);
+        assert_eq!(drops.get(), 4);
+        assert!(lru.pop_lru().is_none());
+    }
+
+    #[test]
+    fn resize_down_evicts_oldest() {
+        let drops = Cell::new(0);
+        let mut lru = StaticLru::<u8, DropCounter<'_>, 4>::new();
+        for i in 0..4 {
+            lru.put(i, DropCounter::new(&drops));
+        }
+        lru.get(&0);
+        lru.resize_down(2);
+        assert_eq!(drops.get(), 2);
+        assert_eq!(lru.capacity(), 2);
+        assert_eq!(lru_keys(&lru).as_slice(), &[0, 3]);
+        assert!(matches!(
+            lru.put(4, DropCounter::new(&drops)),
+            Put::Evicted(3, _)
+        ));
+        assert_eq!(drops.get(), 3);
+
+        lru.resize_down(4);
+        assert!(matches!(
+            lru.put(5, DropCounter::new(&drops)),
+            Put::Inserted
+        ));
+        assert_eq!(lru_keys(&lru).as_slice(), &[5, 4, 0]);
+        assert!(panics(|| lru.resize_down(5)));
+    }
+}
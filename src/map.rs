@@ -0,0 +1,595 @@
+//! Fixed-capacity sorted map.
+
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::iter::FusedIterator;
+use core::ops::{Bound, Range, RangeBounds};
+use core::{fmt, slice};
+
+use crate::{CapacityError, IntoIter as VecIntoIter, StaticVec};
+
+/// Fixed-capacity map, keeping its entries sorted by key in a [`StaticVec`].
+///
+/// Lookups are O(log n), inserts and removals are O(n) as they shift the entries behind.
+pub struct StaticMap<K, V, const N: usize> {
+    data: StaticVec<(K, V), N>,
+}
+
+impl<K, V, const N: usize> StaticMap<K, V, N> {
+    pub const fn new() -> Self {
+        Self {
+            data: StaticVec::new(),
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    /// Returns the entries, sorted by key.
+    pub fn as_slice(&self) -> &[(K, V)] {
+        &self.data
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(&self.data)
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut::new(&mut self.data)
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator + '_ {
+        self.data.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator + '_ {
+        self.data.iter().map(|(_, v)| v)
+    }
+
+    pub fn values_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut V> + ExactSizeIterator + '_ {
+        self.data.iter_mut().map(|(_, v)| v)
+    }
+
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.data.first().map(|(k, v)| (k, v))
+    }
+
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.data.last().map(|(k, v)| (k, v))
+    }
+
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(self.data.remove(0))
+    }
+
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        self.data.pop()
+    }
+
+    /// Keeps only the entries for which `f` returns `true`.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.data.retain_mut(|(k, v)| f(k, v))
+    }
+}
+
+impl<K: Ord, V, const N: usize> StaticMap<K, V, N> {
+    /// Creates a map from a vec of entries. If a key occurs more than once, the last entry wins.
+    pub fn from_vec(data: StaticVec<(K, V), N>) -> Self {
+        // the sort is unstable, so the original positions decide between entries of equal keys
+        let mut indexed = StaticVec::<(usize, (K, V)), N>::new();
+        for it in data.into_iter().enumerate() {
+            //cannot fail, as both vecs have the same capacity
+            let _ = indexed.push(it);
+        }
+        // the last entry of a key sorts first, which is the one dedup keeps
+        indexed.sort_unstable_by(|(i, (a, _)), (j, (b, _))| a.cmp(b).then(j.cmp(i)));
+        indexed.dedup_by(|(_, (a, _)), (_, (b, _))| a == b);
+
+        let mut map = Self::new();
+        for (_, it) in indexed {
+            //cannot fail, as both vecs have the same capacity
+            let _ = map.data.push(it);
+        }
+        map
+    }
+
+    fn search<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.data.binary_search_by(|(k, _)| k.borrow().cmp(key))
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search(key).is_ok()
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let idx = self.search(key).ok()?;
+        let (k, v) = &self.data[idx];
+        Some((k, v))
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let idx = self.search(key).ok()?;
+        Some(&mut self.data.as_mut_slice()[idx].1)
+    }
+
+    /// Inserts `value` under `key`, returning the previous value of `key`.
+    ///
+    /// If the key is new and the map is full, the entry is handed back in the error.
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, CapacityError<(K, V)>> {
+        match self.search(&key) {
+            Ok(idx) => Ok(Some(core::mem::replace(
+                &mut self.data.as_mut_slice()[idx].1,
+                value,
+            ))),
+            Err(idx) => {
+                self.data.try_insert(idx, (key, value))?;
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let idx = self.search(key).ok()?;
+        Some(self.data.remove(idx))
+    }
+
+    /// Returns the entry of `key`, for in-place manipulation.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, N> {
+        match self.search(&key) {
+            Ok(idx) => Entry::Occupied(OccupiedEntry { map: self, idx }),
+            Err(idx) => Entry::Vacant(VacantEntry {
+                map: self,
+                idx,
+                key,
+            }),
+        }
+    }
+
+    /// Returns an iterator over the entries with keys within `range`, in ascending order.
+    ///
+    /// Panics like `BTreeMap::range` if the start of the range is greater than its end, or if
+    /// both are equal and excluded.
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let slice = self.data.as_slice();
+        let range = sorted_range(slice, &range, |(k, _), q| k.borrow().cmp(q));
+        Iter::new(&slice[range])
+    }
+}
+
+/// Returns the positions of the elements of the sorted `slice` within `range`, comparing them
+/// to the bounds with `cmp`.
+///
+/// Panics if the start of the range is greater than its end, or if both are equal and excluded.
+pub(crate) fn sorted_range<T, Q, R, F>(slice: &[T], range: &R, cmp: F) -> Range<usize>
+where
+    Q: Ord + ?Sized,
+    R: RangeBounds<Q>,
+    F: Fn(&T, &Q) -> Ordering,
+{
+    match (range.start_bound(), range.end_bound()) {
+        (Bound::Excluded(s), Bound::Excluded(e)) if s == e => {
+            panic!("range start and end are equal and excluded")
+        }
+        (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e))
+            if s > e =>
+        {
+            panic!("range start is greater than range end")
+        }
+        _ => {}
+    }
+    let start = match range.start_bound() {
+        Bound::Included(s) => slice.partition_point(|it| cmp(it, s).is_lt()),
+        Bound::Excluded(s) => slice.partition_point(|it| cmp(it, s).is_le()),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(e) => slice.partition_point(|it| cmp(it, e).is_le()),
+        Bound::Excluded(e) => slice.partition_point(|it| cmp(it, e).is_lt()),
+        Bound::Unbounded => slice.len(),
+    };
+    start..end
+}
+
+/// Entry of a [`StaticMap`], returned by [`StaticMap::entry`].
+pub enum Entry<'a, K, V, const N: usize> {
+    Occupied(OccupiedEntry<'a, K, V, N>),
+    Vacant(VacantEntry<'a, K, V, N>),
+}
+
+/// Entry of a key present in a [`StaticMap`].
+pub struct OccupiedEntry<'a, K, V, const N: usize> {
+    map: &'a mut StaticMap<K, V, N>,
+    idx: usize,
+}
+
+/// Entry of a key missing in a [`StaticMap`].
+pub struct VacantEntry<'a, K, V, const N: usize> {
+    map: &'a mut StaticMap<K, V, N>,
+    // position the key gets inserted at
+    idx: usize,
+    key: K,
+}
+
+impl<'a, K, V, const N: usize> Entry<'a, K, V, N> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key(),
+        }
+    }
+
+    /// Returns the value, inserting `default` if the key is missing.
+    pub fn or_insert(self, default: V) -> Result<&'a mut V, CapacityError<(K, V)>> {
+        match self {
+            Entry::Occupied(e) => Ok(e.into_mut()),
+            Entry::Vacant(e) => e.insert(default),
+        }
+    }
+
+    /// Returns the value, inserting the result of `default` if the key is missing.
+    pub fn or_insert_with<F>(self, default: F) -> Result<&'a mut V, CapacityError<(K, V)>>
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(e) => Ok(e.into_mut()),
+            Entry::Vacant(e) => e.insert(default()),
+        }
+    }
+
+    /// Returns the value, inserting `V::default()` if the key is missing.
+    pub fn or_default(self) -> Result<&'a mut V, CapacityError<(K, V)>>
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Calls `f` with the value, if the key is present.
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Entry::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V, const N: usize> OccupiedEntry<'a, K, V, N> {
+    pub fn key(&self) -> &K {
+        &self.map.data[self.idx].0
+    }
+
+    pub fn get(&self) -> &V {
+        &self.map.data[self.idx].1
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.data.as_mut_slice()[self.idx].1
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.data.as_mut_slice()[self.idx].1
+    }
+
+    /// Replaces the value and returns the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        core::mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    pub fn remove_entry(self) -> (K, V) {
+        self.map.data.remove(self.idx)
+    }
+}
+
+impl<'a, K, V, const N: usize> VacantEntry<'a, K, V, N> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Inserts `value` under the entry's key, or hands back the entry if the map is full.
+    pub fn insert(self, value: V) -> Result<&'a mut V, CapacityError<(K, V)>> {
+        self.map.data.try_insert(self.idx, (self.key, value))?;
+        Ok(&mut self.map.data.as_mut_slice()[self.idx].1)
+    }
+}
+
+impl<K, V, const N: usize> Default for StaticMap<K, V, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Clone, V: Clone, const N: usize> Clone for StaticMap<K, V, N> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, const N: usize> fmt::Debug for StaticMap<K, V, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq, const N: usize> PartialEq for StaticMap<K, V, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl<K: Eq, V: Eq, const N: usize> Eq for StaticMap<K, V, N> {}
+
+impl<K: PartialOrd, V: PartialOrd, const N: usize> PartialOrd for StaticMap<K, V, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.data.as_slice().partial_cmp(other.data.as_slice())
+    }
+}
+
+impl<K, V, Q, const N: usize> core::ops::Index<&Q> for StaticMap<K, V, N>
+where
+    K: Borrow<Q> + Ord,
+    Q: Ord + ?Sized,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &Self::Output {
+        self.get(key).expect("key not found")
+    }
+}
+
+impl<K: Ord, V, const N: usize> From<StaticVec<(K, V), N>> for StaticMap<K, V, N> {
+    fn from(data: StaticVec<(K, V), N>) -> Self {
+        Self::from_vec(data)
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a StaticMap<K, V, N> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a mut StaticMap<K, V, N> {
+    type Item = (&'a K, &'a mut V);
+
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, const N: usize> IntoIterator for StaticMap<K, V, N> {
+    type Item = (K, V);
+
+    type IntoIter = VecIntoIter<(K, V), N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+/// Iterator over the entries of a map.
+#[derive(Clone, Debug)]
+pub struct Iter<'a, K, V>(slice::Iter<'a, (K, V)>);
+
+impl<'a, K, V> Iter<'a, K, V> {
+    pub(crate) fn new(entries: &'a [(K, V)]) -> Self {
+        Self(entries.iter())
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(k, v)| (k, v))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for Iter<'a, K, V> {}
+
+/// Iterator over the entries of a map, with mutable values.
+#[derive(Debug)]
+pub struct IterMut<'a, K, V>(slice::IterMut<'a, (K, V)>);
+
+impl<'a, K, V> IterMut<'a, K, V> {
+    pub(crate) fn new(entries: &'a mut [(K, V)]) -> Self {
+        Self(entries.iter_mut())
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, v)| (&*k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(k, v)| (&*k, v))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for IterMut<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for IterMut<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use super::*;
+    use crate::test_util::{panics, DropCounter};
+
+    fn map() -> StaticMap<u8, u8, 8> {
+        StaticMap::from_vec(StaticVec::from_array([(10, 1), (30, 3), (50, 5), (70, 7)]))
+    }
+
+    fn range_keys<R: RangeBounds<u8>>(map: &StaticMap<u8, u8, 8>, range: R) -> StaticVec<u8, 8> {
+        let mut keys = StaticVec::new();
+        for (k, _) in map.range(range) {
+            keys.push(*k).unwrap();
+        }
+        keys
+    }
+
+    #[test]
+    fn range_compares_bounds() {
+        let map = map();
+        assert_eq!(range_keys(&map, 30..70).as_slice(), &[30, 50]);
+        assert_eq!(range_keys(&map, 20..=70).as_slice(), &[30, 50, 70]);
+        assert_eq!(range_keys(&map, ..40).as_slice(), &[10, 30]);
+        let excluded = (Bound::Excluded(30), Bound::Unbounded);
+        assert_eq!(range_keys(&map, excluded).as_slice(), &[50, 70]);
+        // equal bounds give an empty range, unless both are excluded
+        assert!(range_keys(&map, 40..40).is_empty());
+        assert!(range_keys(&map, 50..50).is_empty());
+        assert_eq!(range_keys(&map, 50..=50).as_slice(), &[50]);
+        assert!(range_keys(&map, (Bound::Excluded(50), Bound::Included(50))).is_empty());
+    }
+
+    #[test]
+    fn range_panics_like_btree_map() {
+        let map = map();
+        let range = |start, end| panics(|| _ = map.range((start, end)));
+        // both bounds fall between 30 and 50, so only comparing them catches the reversal
+        assert!(range(Bound::Included(45), Bound::Excluded(35)));
+        assert!(range(Bound::Included(70), Bound::Included(10)));
+        assert!(range(Bound::Excluded(40), Bound::Excluded(40)));
+        assert!(range(Bound::Excluded(30), Bound::Excluded(30)));
+        assert!(!range(Bound::Included(40), Bound::Excluded(40)));
+    }
+
+    #[test]
+    fn from_vec_keeps_last_entry_of_a_key() {
+        let drops = Cell::new(0);
+        let mut vec = StaticVec::<_, 8>::new();
+        for (k, v) in [(3, 0), (1, 1), (3, 2), (2, 3), (1, 4), (3, 5)] {
+            vec.push((k, (v, DropCounter::new(&drops)))).unwrap();
+        }
+        let map = StaticMap::from_vec(vec);
+        assert_eq!(drops.get(), 3);
+        let mut entries = StaticVec::<(u8, u8), 8>::new();
+        for (k, (v, _)) in &map {
+            entries.push((*k, *v)).unwrap();
+        }
+        assert_eq!(entries.as_slice(), &[(1, 4), (2, 3), (3, 5)]);
+    }
+
+    #[test]
+    fn entry_inserts_in_order() {
+        let mut map = StaticMap::<u8, u8, 4>::new();
+        *map.entry(5).or_insert(0).unwrap() += 1;
+        *map.entry(5).or_insert(0).unwrap() += 1;
+        map.entry(1).and_modify(|v| *v = 9).or_default().unwrap();
+        assert_eq!(map.as_slice(), &[(1, 0), (5, 2)]);
+        match map.entry(5) {
+            Entry::Occupied(e) => assert_eq!(e.remove_entry(), (5, 2)),
+            Entry::Vacant(_) => unreachable!(),
+        }
+        map.insert(3, 3).unwrap();
+        map.insert(2, 2).unwrap();
+        map.insert(4, 4).unwrap();
+        assert_eq!(map.entry(0).or_insert(0).unwrap_err().into_inner(), (0, 0));
+        assert_eq!(map.insert(4, 40).ok(), Some(Some(4)));
+        assert!(map.keys().eq(&[1, 2, 3, 4]));
+    }
+}
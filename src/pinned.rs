@@ -0,0 +1,198 @@
+//! Fixed-capacity storage whose elements never move, for `!Unpin` values like `async fn`
+//! futures.
+
+use core::marker::PhantomPinned;
+use core::mem::{self, MaybeUninit};
+use core::pin::Pin;
+use core::{fmt, ptr};
+
+use crate::CapacityError;
+
+/// Fixed-capacity storage of pinned elements, addressed by the slot they were inserted into.
+///
+/// Once the storage is pinned, so are its elements: they are never moved, and removal drops
+/// them in place, leaving their slot vacant for a following insert.
+pub struct PinnedVec<T, const N: usize> {
+    data: [MaybeUninit<T>; N],
+    occupied: [bool; N],
+    len: usize,
+    _pin: PhantomPinned,
+}
+
+impl<T, const N: usize> PinnedVec<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            data: [const { MaybeUninit::uninit() }; N],
+            occupied: [false; N],
+            len: 0,
+            _pin: PhantomPinned,
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn contains(&self, slot: usize) -> bool {
+        slot < N && self.occupied[slot]
+    }
+
+    pub fn get(&self, slot: usize) -> Option<&T> {
+        if !self.contains(slot) {
+            return None;
+        }
+        //safe as the slot is occupied
+        Some(unsafe { self.data[slot].assume_init_ref() })
+    }
+
+    pub fn get_pin_mut(self: Pin<&mut Self>, slot: usize) -> Option<Pin<&mut T>> {
+        if !self.contains(slot) {
+            return None;
+        }
+        //safe as the element is pinned along with the storage, and the slot is occupied
+        Some(unsafe { self.map_unchecked_mut(|it| it.data[slot].assume_init_mut()) })
+    }
+
+    /// Inserts `value` into the first vacant slot and returns the slot.
+    ///
+    /// If the storage is full, the value is handed back in the error.
+    pub fn insert(self: Pin<&mut Self>, value: T) -> Result<usize, CapacityError<T>> {
+        //safe as no element is moved
+        let this = unsafe { self.get_unchecked_mut() };
+        let Some(slot) = this.occupied.iter().position(|it| !it) else {
+            return Err(CapacityError::new(value));
+        };
+        this.data[slot].write(value);
+        this.occupied[slot] = true;
+        this.len += 1;
+        Ok(slot)
+    }
+
+    /// Drops the element in `slot` in place, returning whether there was one.
+    pub fn remove(self: Pin<&mut Self>, slot: usize) -> bool {
+        if !self.contains(slot) {
+            return false;
+        }
+        //safe as no element is moved
+        let this = unsafe { self.get_unchecked_mut() };
+        // vacate the slot first, so a panicking drop cannot cause a double drop
+        this.occupied[slot] = false;
+        this.len -= 1;
+        //safe as the slot was occupied
+        unsafe { ptr::drop_in_place(this.data[slot].as_mut_ptr()) };
+        true
+    }
+
+    /// Drops all elements in place.
+    pub fn clear(self: Pin<&mut Self>) {
+        //safe as no element is moved
+        unsafe { self.get_unchecked_mut() }.drop_from(0);
+    }
+
+    /// Drops the elements from `slot` on in place, going on with the rest if one of them panics,
+    /// as pinned elements must be dropped before their memory is freed.
+    fn drop_from(&mut self, slot: usize) {
+        struct Dropper<'a, T, const N: usize>(&'a mut PinnedVec<T, N>, usize);
+
+        impl<'a, T, const N: usize> Drop for Dropper<'a, T, N> {
+            fn drop(&mut self) {
+                self.0.drop_from(self.1);
+            }
+        }
+
+        for i in slot..N {
+            if !self.occupied[i] {
+                continue;
+            }
+            // vacate the slot first, so a panicking drop cannot cause a double drop
+            self.occupied[i] = false;
+            self.len -= 1;
+            let guard = Dropper(self, i + 1);
+            //safe as the slot was occupied
+            unsafe { ptr::drop_in_place(guard.0.data[i].as_mut_ptr()) };
+            mem::forget(guard);
+        }
+    }
+
+    /// Returns an iterator over the occupied slots and their elements.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> + '_ {
+        (0..N).filter_map(|slot| Some((slot, self.get(slot)?)))
+    }
+}
+
+impl<T, const N: usize> Drop for PinnedVec<T, N> {
+    fn drop(&mut self) {
+        self.drop_from(0);
+    }
+}
+
+impl<T, const N: usize> Default for PinnedVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for PinnedVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use core::pin::pin;
+
+    use super::*;
+    use crate::test_util::{panics, DropCounter};
+
+    #[test]
+    fn remove_reuses_slot() {
+        let drops = Cell::new(0);
+        let mut vec = pin!(PinnedVec::<_, 3>::new());
+        for _ in 0..3 {
+            vec.as_mut().insert(DropCounter::new(&drops)).unwrap();
+        }
+        assert!(vec.as_mut().insert(DropCounter::new(&drops)).is_err());
+        assert_eq!(drops.get(), 1);
+        assert!(vec.as_mut().remove(1));
+        assert!(!vec.as_mut().remove(1));
+        assert_eq!(drops.get(), 2);
+        assert_eq!(vec.as_mut().insert(DropCounter::new(&drops)).ok(), Some(1));
+        vec.as_mut().clear();
+        assert_eq!(drops.get(), 5);
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn drop_continues_after_panic() {
+        let drops = Cell::new(0);
+        assert!(panics(|| {
+            let mut vec = pin!(PinnedVec::<_, 4>::new());
+            vec.as_mut().insert(DropCounter::panicking(&drops)).unwrap();
+            vec.as_mut().insert(DropCounter::new(&drops)).unwrap();
+            vec.as_mut().insert(DropCounter::new(&drops)).unwrap();
+        }));
+        assert_eq!(drops.get(), 3);
+    }
+
+    #[test]
+    fn clear_continues_after_panic() {
+        let drops = Cell::new(0);
+        let mut vec = pin!(PinnedVec::<_, 4>::new());
+        vec.as_mut().insert(DropCounter::new(&drops)).unwrap();
+        vec.as_mut().insert(DropCounter::panicking(&drops)).unwrap();
+        vec.as_mut().insert(DropCounter::new(&drops)).unwrap();
+        assert!(panics(|| vec.as_mut().clear()));
+        assert_eq!(drops.get(), 3);
+        assert!(vec.is_empty());
+    }
+}
@@ -0,0 +1,310 @@
+//! Overflow policies, deciding what happens when pushing into a full vec.
+//!
+//! A policy is selected at the type level through the marker parameter of [`PolicyVec`], so the
+//! push path does not branch on it at runtime. It also picks the storage: a [`StaticVec`], or a
+//! [`StaticDeque`] for [`DropOldest`], whose ring makes dropping the front O(1).
+
+use core::marker::PhantomData;
+use core::{fmt, mem};
+
+use crate::{CapacityError, ExtendError, StaticDeque, StaticVec};
+
+/// Decides how [`PolicyVec`] handles pushes into a full vec.
+pub trait OverflowPolicy {
+    /// Storage of the elements.
+    type Storage<T, const N: usize>: PolicyStorage<T, N>;
+    /// Result of a single push.
+    type Push<T>;
+    /// Result of extending from an iterator.
+    type Extend<T, I>;
+
+    fn push<T, const N: usize>(storage: &mut Self::Storage<T, N>, item: T) -> Self::Push<T>;
+
+    fn extend<T, I: Iterator<Item = T>, const N: usize>(
+        storage: &mut Self::Storage<T, N>,
+        iter: I,
+    ) -> Self::Extend<T, I>;
+}
+
+/// Storage of a [`PolicyVec`], convertible from and into a [`StaticVec`].
+pub trait PolicyStorage<T, const N: usize>: From<StaticVec<T, N>> + Into<StaticVec<T, N>> {
+    /// The empty storage, so [`PolicyVec::new`] can be a `const fn`.
+    const EMPTY: Self;
+}
+
+impl<T, const N: usize> PolicyStorage<T, N> for StaticVec<T, N> {
+    const EMPTY: Self = Self::new();
+}
+
+impl<T, const N: usize> PolicyStorage<T, N> for StaticDeque<T, N> {
+    const EMPTY: Self = Self::new();
+}
+
+/// Rejects the incoming element and hands it back, like [`StaticVec::push`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Reject;
+
+/// Removes the oldest (first) element to make room for the incoming one, so the storage keeps
+/// the last `N` elements, e.g. as a history buffer.
+///
+/// The elements are stored in a [`StaticDeque`], so a push into a full storage is O(1).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct DropOldest;
+
+/// Silently discards the incoming element, so the vec keeps the first `N` elements (saturates).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct DropIncoming;
+
+/// Overwrites the newest (last) element with the incoming one.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ReplaceLast;
+
+impl OverflowPolicy for Reject {
+    type Storage<T, const N: usize> = StaticVec<T, N>;
+    type Push<T> = Result<(), CapacityError<T>>;
+    type Extend<T, I> = Result<(), ExtendError<T, I>>;
+
+    fn push<T, const N: usize>(vec: &mut StaticVec<T, N>, item: T) -> Self::Push<T> {
+        vec.push(item)
+    }
+
+    fn extend<T, I: Iterator<Item = T>, const N: usize>(
+        vec: &mut StaticVec<T, N>,
+        iter: I,
+    ) -> Self::Extend<T, I> {
+        vec.try_extend_from_iter(iter)
+    }
+}
+
+impl OverflowPolicy for DropOldest {
+    type Storage<T, const N: usize> = StaticDeque<T, N>;
+    /// The removed oldest element, if the storage was full.
+    type Push<T> = Option<T>;
+    type Extend<T, I> = ();
+
+    fn push<T, const N: usize>(deque: &mut StaticDeque<T, N>, item: T) -> Self::Push<T> {
+        deque.push_back_overwrite(item)
+    }
+
+    fn extend<T, I: Iterator<Item = T>, const N: usize>(
+        deque: &mut StaticDeque<T, N>,
+        iter: I,
+    ) -> Self::Extend<T, I> {
+        for it in iter {
+            deque.push_back_overwrite(it);
+        }
+    }
+}
+
+impl OverflowPolicy for DropIncoming {
+    type Storage<T, const N: usize> = StaticVec<T, N>;
+    /// The discarded incoming element, if the vec was full.
+    type Push<T> = Option<T>;
+    /// Number of discarded elements.
+    type Extend<T, I> = usize;
+
+    fn push<T, const N: usize>(vec: &mut StaticVec<T, N>, item: T) -> Self::Push<T> {
+        vec.push(item).err().map(CapacityError::into_inner)
+    }
+
+    fn extend<T, I: Iterator<Item = T>, const N: usize>(
+        vec: &mut StaticVec<T, N>,
+        iter: I,
+    ) -> Self::Extend<T, I> {
+        match vec.try_extend_from_iter(iter) {
+            Ok(()) => 0,
+            Err(e) => e.into_remaining().count(),
+        }
+    }
+}
+
+impl OverflowPolicy for ReplaceLast {
+    type Storage<T, const N: usize> = StaticVec<T, N>;
+    /// The overwritten newest element, if the vec was full.
+    type Push<T> = Option<T>;
+    type Extend<T, I> = ();
+
+    fn push<T, const N: usize>(vec: &mut StaticVec<T, N>, item: T) -> Self::Push<T> {
+        match vec.push(item) {
+            Ok(()) => None,
+            Err(e) => match vec.last_mut() {
+                Some(last) => Some(mem::replace(last, e.into_inner())),
+                None => Some(e.into_inner()),
+            },
+        }
+    }
+
+    fn extend<T, I: Iterator<Item = T>, const N: usize>(
+        vec: &mut StaticVec<T, N>,
+        iter: I,
+    ) -> Self::Extend<T, I> {
+        for it in iter {
+            Self::push(vec, it);
+        }
+    }
+}
+
+/// [`StaticVec`] whose `push` and `extend` follow the overflow policy `P`.
+///
+/// All other methods of the storage picked by the policy are available through `Deref`: those of
+/// [`StaticDeque`] for [`DropOldest`], and of `StaticVec` otherwise.
+pub struct PolicyVec<T, const N: usize, P: OverflowPolicy = Reject> {
+    storage: P::Storage<T, N>,
+    _policy: PhantomData<P>,
+}
+
+impl<T, const N: usize, P: OverflowPolicy> PolicyVec<T, N, P> {
+    pub const fn new() -> Self {
+        Self {
+            storage: P::Storage::<T, N>::EMPTY,
+            _policy: PhantomData,
+        }
+    }
+
+    /// Moves the elements of `vec` into the storage of the policy.
+    ///
+    /// Unlike [`Self::new`], this is not a `const fn`, as the conversion into the storage is not.
+    pub fn from_vec(vec: StaticVec<T, N>) -> Self {
+        Self {
+            storage: vec.into(),
+            _policy: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> StaticVec<T, N> {
+        self.storage.into()
+    }
+
+    pub fn push(&mut self, item: T) -> P::Push<T> {
+        P::push(&mut self.storage, item)
+    }
+
+    pub fn extend_from_iter<I: IntoIterator<Item = T>>(
+        &mut self,
+        iter: I,
+    ) -> P::Extend<T, I::IntoIter> {
+        P::extend(&mut self.storage, iter.into_iter())
+    }
+}
+
+impl<T, const N: usize, P: OverflowPolicy> core::ops::Deref for PolicyVec<T, N, P> {
+    type Target = P::Storage<T, N>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.storage
+    }
+}
+
+impl<T, const N: usize, P: OverflowPolicy> core::ops::DerefMut for PolicyVec<T, N, P> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.storage
+    }
+}
+
+impl<T, const N: usize, P: OverflowPolicy> Default for PolicyVec<T, N, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize, P: OverflowPolicy> From<StaticVec<T, N>> for PolicyVec<T, N, P> {
+    fn from(vec: StaticVec<T, N>) -> Self {
+        Self::from_vec(vec)
+    }
+}
+
+impl<T, const N: usize, P: OverflowPolicy> Clone for PolicyVec<T, N, P>
+where
+    P::Storage<T, N>: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            storage: self.storage.clone(),
+            _policy: PhantomData,
+        }
+    }
+}
+
+impl<T, const N: usize, P: OverflowPolicy> fmt::Debug for PolicyVec<T, N, P>
+where
+    P::Storage<T, N>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.storage.fmt(f)
+    }
+}
+
+impl<T, const N: usize, P: OverflowPolicy> PartialEq for PolicyVec<T, N, P>
+where
+    P::Storage<T, N>: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.storage == other.storage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use super::*;
+    use crate::test_util::{counters, keys, DropCounter};
+
+    #[test]
+    fn const_new() {
+        static VEC: PolicyVec<u8, 4, DropOldest> = PolicyVec::new();
+        assert!(VEC.is_empty());
+        const REJECT: PolicyVec<u8, 4> = PolicyVec::new();
+        assert_eq!(REJECT.capacity(), 4);
+    }
+
+    #[test]
+    fn drop_oldest_keeps_last_elements() {
+        let mut vec = PolicyVec::<u8, 3, DropOldest>::new();
+        vec.extend_from_iter(0..5);
+        assert_eq!(vec.push(5), Some(2));
+        assert!(vec.iter().eq(&[3, 4, 5]));
+        assert_eq!(vec.into_inner().as_slice(), &[3, 4, 5]);
+        assert_eq!(PolicyVec::<u8, 0, DropOldest>::new().push(1), Some(1));
+    }
+
+    #[test]
+    fn drop_oldest_into_inner_after_pop_front() {
+        let drops = Cell::new(0);
+        let mut vec = PolicyVec::<_, 8, DropOldest>::from_vec(counters(&drops, 3));
+        drop(vec.pop_front());
+        let vec = vec.into_inner();
+        assert_eq!(keys(&vec).as_slice(), &[1, 2]);
+        drop(vec);
+        assert_eq!(drops.get(), 3);
+    }
+
+    #[test]
+    fn drop_incoming_saturates() {
+        let drops = Cell::new(0);
+        let mut vec = PolicyVec::<_, 8, DropIncoming>::from_vec(counters(&drops, 6));
+        assert!(vec.push((6, DropCounter::new(&drops))).is_none());
+        let iter = (7..10).map(|i| (i, DropCounter::new(&drops)));
+        assert_eq!(vec.extend_from_iter(iter), 2);
+        assert_eq!(drops.get(), 2);
+        let rejected = vec.push((10, DropCounter::new(&drops)));
+        assert_eq!(rejected.map(|it| it.0), Some(10));
+        assert_eq!(keys(&vec).as_slice(), &[0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(drops.get(), 3);
+    }
+
+    #[test]
+    fn replace_last_overwrites_newest() {
+        let mut vec = PolicyVec::<u8, 3, ReplaceLast>::new();
+        assert_eq!(vec.push(0), None);
+        vec.extend_from_iter(1..5);
+        assert_eq!(vec.as_slice(), &[0, 1, 4]);
+        assert_eq!(vec.push(5), Some(4));
+        assert_eq!(vec.as_slice(), &[0, 1, 5]);
+
+        let mut empty = PolicyVec::<u8, 0, ReplaceLast>::new();
+        assert_eq!(empty.push(1), Some(1));
+        empty.extend_from_iter(0..3);
+        assert!(empty.is_empty());
+    }
+}
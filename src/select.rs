@@ -0,0 +1,1115 @@
+//! Futures completing with the first ready future of a [`StaticVec`], a [`StaticSlab`] or a
+//! [`PinnedVec`].
+
+use core::cell::UnsafeCell;
+use core::future::Future;
+use core::mem::MaybeUninit;
+use core::pin::Pin;
+use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use either::Either;
+
+use crate::{CapacityError, PinnedVec, StaticSlab, StaticVec};
+
+/// Future polling every future of a vec, completing with the output of the first ready one,
+/// which is removed from the vec.
+///
+/// A fair select starts each poll one future further than the previous poll, and after the
+/// future that completed, so no future can starve the others. A biased select always starts
+/// with the first future.
+///
+/// The select can be awaited repeatedly through `&mut`, keeping its position in the rotation:
+/// `loop { let out = (&mut select).await; }`.
+pub struct SelectVec<'a, T, const N: usize, const INDEXED: bool = false> {
+    vec: &'a mut StaticVec<T, N>,
+    rotation: Rotation<N>,
+}
+
+/// [`SelectVec`] also returning the index the completed future had in the vec, created with
+/// [`SelectVec::indexed`].
+///
+/// The futures behind the completed one move one index down when it is removed, so the index
+/// only identifies the future at the time it completed. Use [`SelectSlab`] for stable keys.
+pub type SelectVecIndexed<'a, T, const N: usize> = SelectVec<'a, T, N, true>;
+
+impl<'a, T, const N: usize> SelectVec<'a, T, N> {
+    /// Creates a fair select.
+    pub fn new(vec: &'a mut StaticVec<T, N>) -> Self {
+        Self {
+            vec,
+            rotation: Rotation::new(false),
+        }
+    }
+
+    /// Creates a select always polling the futures in the vec order.
+    pub fn biased(vec: &'a mut StaticVec<T, N>) -> Self {
+        Self {
+            vec,
+            rotation: Rotation::new(true),
+        }
+    }
+
+    /// Makes the select also return the index the completed future had in the vec.
+    pub fn indexed(self) -> SelectVecIndexed<'a, T, N> {
+        SelectVec {
+            vec: self.vec,
+            rotation: self.rotation,
+        }
+    }
+}
+
+impl<'a, T, const N: usize, const INDEXED: bool> SelectVec<'a, T, N, INDEXED> {
+    /// Polls the futures with their own wakers from `wakers`, so only the woken ones are polled
+    /// again.
+    pub fn with_wakers(mut self, wakers: &'static SelectWakers<N>) -> Self {
+        self.rotation.set_wakers(wakers);
+        self
+    }
+
+    /// Adds `fut` to the futures polled by the select, e.g. between awaits.
+    ///
+    /// If the vec is full, the future is handed back in the error.
+    pub fn push(&mut self, fut: T) -> Result<(), CapacityError<T>> {
+        self.vec.push(fut)
+    }
+
+    pub fn get(&self) -> &StaticVec<T, N> {
+        self.vec
+    }
+
+    /// Returns the vec, e.g. to rearrange the futures between awaits.
+    ///
+    /// As the futures may be moved, each of them is polled again on the next poll. Use
+    /// [`Self::push`] to only add futures.
+    pub fn get_mut(&mut self) -> &mut StaticVec<T, N> {
+        self.rotation.reset();
+        self.vec
+    }
+
+    pub fn into_inner(self) -> &'a mut StaticVec<T, N> {
+        self.vec
+    }
+}
+
+impl<'a, T, const N: usize> Future for SelectVec<'a, T, N>
+where
+    T: Future + Unpin,
+{
+    type Output = T::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let self_mut = self.get_mut();
+        poll_vec(self_mut.vec, &mut self_mut.rotation, cx).map(|(_, x)| x)
+    }
+}
+
+impl<'a, T, const N: usize> Future for SelectVecIndexed<'a, T, N>
+where
+    T: Future + Unpin,
+{
+    type Output = (usize, T::Output);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let self_mut = self.get_mut();
+        poll_vec(self_mut.vec, &mut self_mut.rotation, cx)
+    }
+}
+
+/// Polls the futures of `vec` in the order of `rotation`, removing the first ready one and
+/// returning its index along with its output.
+fn poll_vec<T, const N: usize>(
+    vec: &mut StaticVec<T, N>,
+    rotation: &mut Rotation<N>,
+    cx: &mut Context<'_>,
+) -> Poll<(usize, T::Output)>
+where
+    T: Future + Unpin,
+{
+    let len = vec.len();
+    let start = if rotation.biased || len == 0 {
+        0
+    } else {
+        rotation.cursor % len
+    };
+    rotation.begin(len, cx);
+    for k in 0..len {
+        let i = (start + k) % len;
+        let fut = Pin::new(&mut vec.as_mut_slice()[i]);
+        if let Poll::Ready(x) = rotation.poll(fut, rotation.slot(i), cx) {
+            vec.remove(i);
+            rotation.removed(i);
+            return Poll::Ready((i, x));
+        }
+    }
+    rotation.pending(start);
+
+    Poll::Pending
+}
+
+/// Future polling every future of a slab, completing with the key and the output of the first
+/// ready one, which is removed from the slab.
+///
+/// The keys of the other futures stay valid, so the key identifies the completed future the
+/// same way it did when the future was inserted. Fairness works like in [`SelectVec`].
+pub struct SelectSlab<'a, T, const N: usize> {
+    slab: &'a mut StaticSlab<T, N>,
+    cursor: usize,
+    biased: bool,
+}
+
+impl<'a, T, const N: usize> SelectSlab<'a, T, N> {
+    /// Creates a fair select.
+    pub fn new(slab: &'a mut StaticSlab<T, N>) -> Self {
+        Self {
+            slab,
+            cursor: 0,
+            biased: false,
+        }
+    }
+
+    /// Creates a select always polling the futures in the key order.
+    pub fn biased(slab: &'a mut StaticSlab<T, N>) -> Self {
+        Self {
+            slab,
+            cursor: 0,
+            biased: true,
+        }
+    }
+
+    /// Returns the slab, e.g. to insert more futures between awaits.
+    pub fn get_mut(&mut self) -> &mut StaticSlab<T, N> {
+        self.slab
+    }
+
+    pub fn into_inner(self) -> &'a mut StaticSlab<T, N> {
+        self.slab
+    }
+}
+
+impl<'a, T, const N: usize> Future for SelectSlab<'a, T, N>
+where
+    T: Future + Unpin,
+{
+    type Output = (usize, T::Output);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let self_mut = self.get_mut();
+        let start = if self_mut.biased || N == 0 {
+            0
+        } else {
+            self_mut.cursor % N
+        };
+        // the keys from the cursor on go first, then the ones before it
+        let ready = self_mut
+            .slab
+            .iter_mut()
+            .skip_while(|(key, _)| *key < start)
+            .find_map(|(key, fut)| poll_ready(key, fut, cx))
+            .or_else(|| {
+                self_mut
+                    .slab
+                    .iter_mut()
+                    .take_while(|(key, _)| *key < start)
+                    .find_map(|(key, fut)| poll_ready(key, fut, cx))
+            });
+        match ready {
+            Some((key, x)) => {
+                self_mut.slab.remove(key);
+                self_mut.cursor = key + 1;
+                Poll::Ready((key, x))
+            }
+            None => {
+                self_mut.cursor = start + 1;
+                Poll::Pending
+            }
+        }
+    }
+}
+
+fn poll_ready<T>(key: usize, fut: &mut T, cx: &mut Context<'_>) -> Option<(usize, T::Output)>
+where
+    T: Future + Unpin,
+{
+    match Pin::new(fut).poll(cx) {
+        Poll::Ready(x) => Some((key, x)),
+        Poll::Pending => None,
+    }
+}
+
+/// Future completing with the output of either an extra future, or the first ready future of
+/// a vec, which is removed from the vec.
+///
+/// A fair select rotates over the extra future and the vec like [`SelectVec`], with the extra
+/// future taking its turn after the last future of the vec. A biased select always polls the
+/// extra future first, then the vec in order.
+pub struct SelectVecAndFut<'a, T, F, const N: usize> {
+    vec: &'a mut StaticVec<T, N>,
+    fut: F,
+    rotation: Rotation<N>,
+}
+
+impl<'a, T, F, const N: usize> SelectVecAndFut<'a, T, F, N> {
+    /// Creates a fair select.
+    pub fn new(vec: &'a mut StaticVec<T, N>, fut: F) -> Self {
+        Self {
+            vec,
+            fut,
+            rotation: Rotation::new(false),
+        }
+    }
+
+    /// Creates a select always polling the extra future first, then the vec in order.
+    pub fn biased(vec: &'a mut StaticVec<T, N>, fut: F) -> Self {
+        Self {
+            vec,
+            fut,
+            rotation: Rotation::new(true),
+        }
+    }
+
+    /// Polls the futures of the vec with their own wakers from `wakers`, so only the woken ones
+    /// are polled again. The extra future is polled on every wakeup.
+    pub fn with_wakers(mut self, wakers: &'static SelectWakers<N>) -> Self {
+        self.rotation.set_wakers(wakers);
+        self
+    }
+
+    /// Adds `fut` to the futures polled by the select, e.g. between awaits.
+    ///
+    /// If the vec is full, the future is handed back in the error.
+    pub fn push(&mut self, fut: T) -> Result<(), CapacityError<T>> {
+        self.vec.push(fut)
+    }
+
+    pub fn get(&self) -> &StaticVec<T, N> {
+        self.vec
+    }
+
+    /// Returns the vec, e.g. to rearrange the futures between awaits.
+    ///
+    /// As the futures may be moved, each of them is polled again on the next poll. Use
+    /// [`Self::push`] to only add futures.
+    pub fn get_mut(&mut self) -> &mut StaticVec<T, N> {
+        self.rotation.reset();
+        self.vec
+    }
+
+    pub fn into_inner(self) -> (&'a mut StaticVec<T, N>, F) {
+        (self.vec, self.fut)
+    }
+}
+
+impl<'a, T, F, const N: usize> Future for SelectVecAndFut<'a, T, F, N>
+where
+    T: Future + Unpin,
+    F: Future + Unpin,
+{
+    type Output = Either<F::Output, T::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let self_mut = self.get_mut();
+        // position `len` stands for the extra future, which goes first when biased
+        let len = self_mut.vec.len();
+        let start = if self_mut.rotation.biased {
+            len
+        } else {
+            self_mut.rotation.cursor % (len + 1)
+        };
+        self_mut.rotation.begin(len, cx);
+        for k in 0..=len {
+            let i = (start + k) % (len + 1);
+            if i == len {
+                if let Poll::Ready(x) = Pin::new(&mut self_mut.fut).poll(cx) {
+                    self_mut.rotation.cursor = 0;
+                    return Poll::Ready(Either::Left(x));
+                }
+                continue;
+            }
+            let fut = &mut self_mut.vec.as_mut_slice()[i];
+            let slot = self_mut.rotation.slot(i);
+            if let Poll::Ready(x) = self_mut.rotation.poll(Pin::new(fut), slot, cx) {
+                self_mut.vec.remove(i);
+                self_mut.rotation.removed(i);
+                return Poll::Ready(Either::Right(x));
+            }
+        }
+        self_mut.rotation.pending(start);
+
+        Poll::Pending
+    }
+}
+
+/// Future polling every future of a pinned vec, completing with the slot and the output of the
+/// first ready one, which is dropped in place.
+///
+/// Unlike the other selects, the futures do not have to be `Unpin`, so `async fn` futures can be
+/// used without boxing. The slots of the other futures stay the same. Fairness and wakers work
+/// like in [`SelectVec`].
+pub struct SelectPinned<'a, T, const N: usize> {
+    vec: Pin<&'a mut PinnedVec<T, N>>,
+    rotation: Rotation<N>,
+}
+
+impl<'a, T, const N: usize> SelectPinned<'a, T, N> {
+    /// Creates a fair select.
+    pub fn new(vec: Pin<&'a mut PinnedVec<T, N>>) -> Self {
+        Self {
+            vec,
+            rotation: Rotation::new(false),
+        }
+    }
+
+    /// Creates a select always polling the futures in the slot order.
+    pub fn biased(vec: Pin<&'a mut PinnedVec<T, N>>) -> Self {
+        Self {
+            vec,
+            rotation: Rotation::new(true),
+        }
+    }
+
+    /// Polls the futures with their own wakers from `wakers`, so only the woken ones are polled
+    /// again.
+    pub fn with_wakers(mut self, wakers: &'static SelectWakers<N>) -> Self {
+        self.rotation.set_wakers(wakers);
+        self
+    }
+
+    /// Adds `fut` to the futures polled by the select, e.g. between awaits, and returns its
+    /// slot.
+    ///
+    /// If the vec is full, the future is handed back in the error.
+    pub fn insert(&mut self, fut: T) -> Result<usize, CapacityError<T>> {
+        let slot = self.vec.as_mut().insert(fut)?;
+        self.rotation.wake(slot);
+        Ok(slot)
+    }
+
+    pub fn get(&self) -> &PinnedVec<T, N> {
+        &self.vec
+    }
+
+    /// Returns the vec, e.g. to remove futures between awaits.
+    ///
+    /// As vacant slots may be filled, each future is polled again on the next poll. Use
+    /// [`Self::insert`] to only add futures.
+    pub fn get_mut(&mut self) -> Pin<&mut PinnedVec<T, N>> {
+        self.rotation.reset();
+        self.vec.as_mut()
+    }
+
+    pub fn into_inner(self) -> Pin<&'a mut PinnedVec<T, N>> {
+        self.vec
+    }
+}
+
+impl<'a, T, const N: usize> Future for SelectPinned<'a, T, N>
+where
+    T: Future,
+{
+    type Output = (usize, T::Output);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let self_mut = self.get_mut();
+        let start = if self_mut.rotation.biased || N == 0 {
+            0
+        } else {
+            self_mut.rotation.cursor % N
+        };
+        self_mut.rotation.register(cx);
+        for k in 0..N {
+            let i = (start + k) % N;
+            let Some(fut) = self_mut.vec.as_mut().get_pin_mut(i) else {
+                continue;
+            };
+            if let Poll::Ready(x) = self_mut.rotation.poll(fut, i, cx) {
+                self_mut.vec.as_mut().remove(i);
+                // the slots do not move, so the rotation goes on behind the completed one
+                self_mut.rotation.cursor = i + 1;
+                return Poll::Ready((i, x));
+            }
+        }
+        self_mut.rotation.pending(start);
+
+        Poll::Pending
+    }
+}
+
+/// Stream of the outputs of the futures in a vec, in the order they complete.
+///
+/// Completed futures are removed from the vec, and new ones can be pushed while the stream is
+/// polled. The stream ends once the vec is empty, but can be polled again after a push.
+/// Fairness and wakers work like in [`SelectVec`]. With the `futures` feature, it implements
+/// `futures_core::Stream`.
+pub struct SelectAll<T, const N: usize> {
+    vec: StaticVec<T, N>,
+    rotation: Rotation<N>,
+}
+
+impl<T, const N: usize> SelectAll<T, N> {
+    /// Creates a fair stream.
+    pub const fn new(vec: StaticVec<T, N>) -> Self {
+        Self {
+            vec,
+            rotation: Rotation::new(false),
+        }
+    }
+
+    /// Creates a stream always polling the futures in the vec order.
+    pub const fn biased(vec: StaticVec<T, N>) -> Self {
+        Self {
+            vec,
+            rotation: Rotation::new(true),
+        }
+    }
+
+    /// Polls the futures with their own wakers from `wakers`, so only the woken ones are polled
+    /// again.
+    pub fn with_wakers(mut self, wakers: &'static SelectWakers<N>) -> Self {
+        self.rotation.set_wakers(wakers);
+        self
+    }
+
+    pub const fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// Adds `fut` to the futures polled by the stream.
+    ///
+    /// If the vec is full, the future is handed back in the error.
+    pub fn push(&mut self, fut: T) -> Result<(), CapacityError<T>> {
+        self.vec.push(fut)
+    }
+
+    /// Returns the futures that did not complete yet.
+    pub fn into_inner(self) -> StaticVec<T, N> {
+        self.vec
+    }
+}
+
+impl<T, const N: usize> SelectAll<T, N>
+where
+    T: Future + Unpin,
+{
+    /// Returns the output of the next completed future, or `None` once there are no futures.
+    pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<T::Output>> {
+        if self.vec.is_empty() {
+            return Poll::Ready(None);
+        }
+        poll_vec(&mut self.vec, &mut self.rotation, cx).map(|(_, x)| Some(x))
+    }
+}
+
+impl<T, const N: usize> Default for SelectAll<T, N> {
+    fn default() -> Self {
+        Self::new(StaticVec::new())
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<T, const N: usize> futures_core::Stream for SelectAll<T, N>
+where
+    T: Future + Unpin,
+{
+    type Item = T::Output;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_next(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // every future yields exactly one item
+        (self.len(), Some(self.len()))
+    }
+}
+
+/// Polling state of a select over a vec, shared by its fair and biased modes.
+struct Rotation<const N: usize> {
+    // index to start the next poll at, when fair
+    cursor: usize,
+    biased: bool,
+    wakers: Option<Claim<N>>,
+    // waker slot of the future at each index of the vec, moved along with the futures
+    slots: StaticVec<usize, N>,
+    // whether a waker slot belongs to a future of the vec
+    used: [bool; N],
+}
+
+impl<const N: usize> Rotation<N> {
+    const fn new(biased: bool) -> Self {
+        Self {
+            cursor: 0,
+            biased,
+            wakers: None,
+            slots: StaticVec::new(),
+            used: [false; N],
+        }
+    }
+
+    /// Claims `wakers` for this select.
+    ///
+    /// Panics if they are used by another select.
+    fn set_wakers(&mut self, wakers: &'static SelectWakers<N>) {
+        // release the previous set first, in case it is the same one
+        self.wakers = None;
+        let claimed = wakers.in_use.swap(true, Ordering::Acquire);
+        assert!(
+            !claimed,
+            "SelectWakers used by two selects at the same time"
+        );
+        self.wakers = Some(Claim(wakers));
+        self.reset();
+    }
+
+    /// Forgets which waker slot belongs to which future, so every future is polled again.
+    fn reset(&mut self) {
+        self.slots.clear();
+        self.used = [false; N];
+        if let Some(Claim(wakers)) = self.wakers {
+            for slot in &wakers.slots {
+                slot.ready.store(true, Ordering::Release);
+            }
+        }
+    }
+
+    /// Prepares polling a vec of `len` futures, giving the futures pushed since the last poll a
+    /// waker slot of their own.
+    fn begin(&mut self, len: usize, cx: &mut Context<'_>) {
+        let Some(Claim(wakers)) = self.wakers else {
+            return;
+        };
+        wakers.parent.register(cx.waker());
+        if self.slots.len() > len {
+            self.reset();
+        }
+        while self.slots.len() < len {
+            //cannot fail, as there are as many slots as futures fit into the vec
+            let slot = self.used.iter().position(|it| !it).unwrap();
+            self.used[slot] = true;
+            wakers.slots[slot].ready.store(true, Ordering::Release);
+            let _ = self.slots.push(slot);
+        }
+    }
+
+    /// Registers the task of the select, for a vec whose futures keep their waker slot.
+    fn register(&self, cx: &mut Context<'_>) {
+        if let Some(Claim(wakers)) = self.wakers {
+            wakers.parent.register(cx.waker());
+        }
+    }
+
+    /// Flags the future owning the waker slot `slot` for the next poll.
+    fn wake(&self, slot: usize) {
+        if let Some(Claim(wakers)) = self.wakers {
+            wakers.slots[slot].ready.store(true, Ordering::Release);
+        }
+    }
+
+    /// Returns the waker slot of the future at index `i` of the vec.
+    fn slot(&self, i: usize) -> usize {
+        match self.wakers {
+            Some(_) => self.slots[i],
+            None => i,
+        }
+    }
+
+    /// Polls the future owning the waker slot `slot`, unless its waker was not woken.
+    fn poll<T>(&self, fut: Pin<&mut T>, slot: usize, cx: &mut Context<'_>) -> Poll<T::Output>
+    where
+        T: Future,
+    {
+        match self.wakers {
+            None => fut.poll(cx),
+            Some(Claim(wakers)) => {
+                if !wakers.slots[slot].ready.swap(false, Ordering::AcqRel) {
+                    return Poll::Pending;
+                }
+                let waker = wakers.waker(slot);
+                fut.poll(&mut Context::from_waker(&waker))
+            }
+        }
+    }
+
+    /// Records that the future at index `i` of the vec completed and was removed.
+    fn removed(&mut self, i: usize) {
+        // the future behind the completed one slid into its place
+        self.cursor = i;
+        if self.wakers.is_some() {
+            let slot = self.slots.remove(i);
+            self.used[slot] = false;
+        }
+    }
+
+    /// Records that no future was ready.
+    fn pending(&mut self, start: usize) {
+        self.cursor = start + 1;
+    }
+}
+
+/// Exclusive use of a [`SelectWakers`] by a select, released on drop.
+struct Claim<const N: usize>(&'static SelectWakers<N>);
+
+impl<const N: usize> Drop for Claim<N> {
+    fn drop(&mut self) {
+        self.0.in_use.store(false, Ordering::Release);
+    }
+}
+
+/// Per-future wakers for a select over a vec of up to `N` futures.
+///
+/// Each future is polled with a waker of its own, which flags it as ready and wakes the task
+/// of the select. On wakeup the select then polls only the flagged futures, instead of all of
+/// them. The wakers point into the set, so it has to be a `static`:
+///
+/// ```
+/// use simplestaticvec::{SelectVec, SelectWakers, StaticVec};
+///
+/// static WAKERS: SelectWakers<32> = SelectWakers::new();
+///
+/// async fn sum<T>(futures: &mut StaticVec<T, 32>) -> u32
+/// where
+///     T: core::future::Future<Output = u32> + Unpin,
+/// {
+///     // one select for all futures, so each await only polls the woken ones
+///     let mut select = SelectVec::new(futures).with_wakers(&WAKERS);
+///     let mut sum = 0;
+///     while !select.get().is_empty() {
+///         sum += (&mut select).await;
+///     }
+///     sum
+/// }
+/// ```
+///
+/// A set can only be used by one select at a time, as they would take each other's wakeups.
+/// It is released when the select is dropped.
+#[repr(C)]
+pub struct SelectWakers<const N: usize> {
+    // first field, so a pointer to a slot leads back to the set
+    slots: [SlotWaker; N],
+    parent: AtomicWaker,
+    in_use: AtomicBool,
+}
+
+struct SlotWaker {
+    ready: AtomicBool,
+    index: usize,
+}
+
+impl<const N: usize> SelectWakers<N> {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(
+        Self::clone_raw,
+        Self::wake_raw,
+        Self::wake_raw,
+        Self::drop_raw,
+    );
+
+    pub const fn new() -> Self {
+        let mut slots = [const { MaybeUninit::<SlotWaker>::uninit() }; N];
+        let mut i = 0;
+        while i < N {
+            slots[i] = MaybeUninit::new(SlotWaker {
+                ready: AtomicBool::new(true),
+                index: i,
+            });
+            i += 1;
+        }
+        Self {
+            //safe as every slot was initialized above
+            slots: unsafe { ptr::read(&slots as *const _ as *const [SlotWaker; N]) },
+            parent: AtomicWaker::new(),
+            in_use: AtomicBool::new(false),
+        }
+    }
+
+    fn waker(&'static self, i: usize) -> Waker {
+        //safe as i < N, and the pointer is derived from the whole set, which lives forever
+        let data = unsafe { (self as *const Self).cast::<SlotWaker>().add(i) };
+        //safe as the vtable functions only read through the pointer, which stays valid
+        unsafe { Waker::from_raw(RawWaker::new(data.cast(), &Self::VTABLE)) }
+    }
+
+    unsafe fn clone_raw(data: *const ()) -> RawWaker {
+        RawWaker::new(data, &Self::VTABLE)
+    }
+
+    unsafe fn wake_raw(data: *const ()) {
+        let slot = data.cast::<SlotWaker>();
+        let index = (*slot).index;
+        (*slot).ready.store(true, Ordering::Release);
+        let set = slot.sub(index).cast::<Self>();
+        (*set).parent.wake();
+    }
+
+    unsafe fn drop_raw(_: *const ()) {}
+}
+
+impl<const N: usize> Default for SelectWakers<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Waker slot which can be woken from another thread or an interrupt while it is registered.
+struct AtomicWaker {
+    state: AtomicUsize,
+    waker: UnsafeCell<Option<Waker>>,
+}
+
+//safe as the waker is only accessed by whoever moved the state away from WAITING
+unsafe impl Sync for AtomicWaker {}
+
+impl AtomicWaker {
+    const WAITING: usize = 0;
+    const REGISTERING: usize = 1;
+    const WAKING: usize = 2;
+
+    const fn new() -> Self {
+        Self {
+            state: AtomicUsize::new(Self::WAITING),
+            waker: UnsafeCell::new(None),
+        }
+    }
+
+    fn register(&self, waker: &Waker) {
+        match self.state.compare_exchange(
+            Self::WAITING,
+            Self::REGISTERING,
+            Ordering::Acquire,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => {
+                //safe as the REGISTERING state gives exclusive access
+                let slot = unsafe { &mut *self.waker.get() };
+                match slot {
+                    Some(old) if old.will_wake(waker) => {}
+                    _ => *slot = Some(waker.clone()),
+                }
+                let res = self.state.compare_exchange(
+                    Self::REGISTERING,
+                    Self::WAITING,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                );
+                if res.is_err() {
+                    // woken while registering, so the wakeup is passed on here
+                    let waker = slot.take();
+                    self.state.swap(Self::WAITING, Ordering::AcqRel);
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                }
+            }
+            // woken right now, so poll again
+            Err(Self::WAKING) => waker.wake_by_ref(),
+            Err(_) => {}
+        }
+    }
+
+    fn wake(&self) {
+        if self.state.fetch_or(Self::WAKING, Ordering::AcqRel) == Self::WAITING {
+            //safe as the WAKING state gives exclusive access
+            let waker = unsafe { (*self.waker.get()).take() };
+            self.state.fetch_and(!Self::WAKING, Ordering::Release);
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::{Cell, RefCell};
+
+    use super::*;
+    use crate::test_util::panics;
+
+    /// State of a [`Probe`], shared with the test.
+    #[derive(Debug, Default)]
+    struct Shared {
+        polls: Cell<usize>,
+        done: Cell<bool>,
+        waker: RefCell<Option<Waker>>,
+    }
+
+    impl Shared {
+        fn complete(&self) {
+            self.done.set(true);
+            if let Some(waker) = self.waker.borrow_mut().take() {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Future counting its polls and completing with its id once told to.
+    #[derive(Debug)]
+    struct Probe<'a>(usize, &'a Shared);
+
+    impl<'a> Future for Probe<'a> {
+        type Output = usize;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<usize> {
+            let shared = self.1;
+            shared.polls.set(shared.polls.get() + 1);
+            if shared.done.get() {
+                return Poll::Ready(self.0);
+            }
+            *shared.waker.borrow_mut() = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+
+    fn noop_waker() -> Waker {
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(
+            |_| RawWaker::new(ptr::null(), &VTABLE),
+            |_| {},
+            |_| {},
+            |_| {},
+        );
+        //safe as the vtable functions do nothing
+        unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE)) }
+    }
+
+    fn poll<F: Future + Unpin>(fut: &mut F, cx: &mut Context<'_>) -> Poll<F::Output> {
+        Pin::new(fut).poll(cx)
+    }
+
+    fn polls(shared: &[Shared]) -> [usize; 8] {
+        core::array::from_fn(|i| shared[i].polls.get())
+    }
+
+    fn probes(shared: &[Shared], n: usize) -> StaticVec<Probe<'_>, 4> {
+        let mut vec = StaticVec::new();
+        for (i, it) in shared.iter().enumerate().take(n) {
+            vec.push(Probe(i, it)).unwrap();
+        }
+        vec
+    }
+
+    #[test]
+    fn fair_select_rotates_across_polls() {
+        let shared: [Shared; 8] = Default::default();
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let mut vec = probes(&shared, 3);
+        let mut select = SelectVec::new(&mut vec);
+        assert_eq!(poll(&mut select, &mut cx), Poll::Pending);
+        assert_eq!(poll(&mut select, &mut cx), Poll::Pending);
+        shared[..3].iter().for_each(Shared::complete);
+        // the third poll starts two futures further, then goes on after the completed one
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(2));
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(0));
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(1));
+
+        let mut vec = probes(&shared[3..], 2);
+        let mut select = SelectVecAndFut::new(&mut vec, Probe(2, &shared[5]));
+        assert_eq!(poll(&mut select, &mut cx), Poll::Pending);
+        shared[3].complete();
+        shared[4].complete();
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(Either::Right(1)));
+        // the extra future takes its turn after the last future of the vec
+        shared[5].complete();
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(Either::Left(2)));
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(Either::Right(0)));
+    }
+
+    #[test]
+    fn biased_select_starts_with_first() {
+        let shared: [Shared; 8] = Default::default();
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let mut vec = probes(&shared, 3);
+        let mut select = SelectVec::biased(&mut vec);
+        assert_eq!(poll(&mut select, &mut cx), Poll::Pending);
+        assert_eq!(poll(&mut select, &mut cx), Poll::Pending);
+        shared[..3].iter().for_each(Shared::complete);
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(0));
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(1));
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(2));
+
+        let mut vec = probes(&shared[3..], 2);
+        let mut select = SelectVecAndFut::biased(&mut vec, Probe(2, &shared[5]));
+        assert_eq!(poll(&mut select, &mut cx), Poll::Pending);
+        shared[3].complete();
+        shared[4].complete();
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(Either::Right(0)));
+        shared[5].complete();
+        // the extra future goes first as long as it is ready
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(Either::Left(2)));
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(Either::Left(2)));
+        assert_eq!(select.get().len(), 1);
+    }
+
+    #[test]
+    fn wakers_only_poll_woken_futures() {
+        static WAKERS: SelectWakers<8> = SelectWakers::new();
+        let shared: [Shared; 8] = Default::default();
+        let mut vec = StaticVec::<_, 8>::new();
+        for (i, it) in shared.iter().enumerate().take(7) {
+            vec.push(Probe(i, it)).unwrap();
+        }
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut select = SelectVec::new(&mut vec).with_wakers(&WAKERS);
+
+        assert_eq!(poll(&mut select, &mut cx), Poll::Pending);
+        assert_eq!(polls(&shared), [1, 1, 1, 1, 1, 1, 1, 0]);
+
+        shared[0].complete();
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(0));
+        assert_eq!(poll(&mut select, &mut cx), Poll::Pending);
+        // the futures behind the completed one moved, but were not woken
+        assert_eq!(polls(&shared), [2, 1, 1, 1, 1, 1, 1, 0]);
+
+        shared[5].complete();
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(5));
+        select.push(Probe(7, &shared[7])).unwrap();
+        assert_eq!(poll(&mut select, &mut cx), Poll::Pending);
+        assert_eq!(polls(&shared), [2, 1, 1, 1, 1, 2, 1, 1]);
+
+        shared[6].complete();
+        shared[3].complete();
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(3));
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready(6));
+        assert_eq!(poll(&mut select, &mut cx), Poll::Pending);
+        assert_eq!(polls(&shared), [2, 1, 1, 2, 1, 2, 2, 1]);
+        assert_eq!(select.get().len(), 4);
+    }
+
+    #[test]
+    fn pinned_wakers_only_poll_woken_futures() {
+        static WAKERS: SelectWakers<8> = SelectWakers::new();
+        let shared: [Shared; 8] = Default::default();
+        let mut vec = core::pin::pin!(PinnedVec::<_, 8>::new());
+        for (i, it) in shared.iter().enumerate().take(4) {
+            vec.as_mut().insert(Probe(i, it)).unwrap();
+        }
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut select = SelectPinned::new(vec.as_mut()).with_wakers(&WAKERS);
+
+        assert_eq!(poll(&mut select, &mut cx), Poll::Pending);
+        shared[2].complete();
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready((2, 2)));
+        assert_eq!(select.insert(Probe(4, &shared[4])).ok(), Some(2));
+        assert_eq!(poll(&mut select, &mut cx), Poll::Pending);
+        assert_eq!(polls(&shared), [1, 1, 2, 1, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn indexed_returns_index_at_completion() {
+        let shared: [Shared; 8] = Default::default();
+        let mut vec = StaticVec::<_, 4>::new();
+        for (i, it) in shared.iter().enumerate().take(4) {
+            vec.push(Probe(i, it)).unwrap();
+        }
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut select = SelectVec::biased(&mut vec).indexed();
+
+        assert_eq!(poll(&mut select, &mut cx), Poll::Pending);
+        shared[2].complete();
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready((2, 2)));
+        // the future behind the completed one moved down
+        shared[3].complete();
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready((2, 3)));
+        assert_eq!(select.into_inner().len(), 2);
+    }
+
+    #[test]
+    fn slab_reports_stable_keys() {
+        let shared: [Shared; 8] = Default::default();
+        let mut slab = StaticSlab::<_, 4>::new();
+        for (i, it) in shared.iter().enumerate().take(4) {
+            assert_eq!(slab.insert(Probe(i, it)).ok(), Some(i));
+        }
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut select = SelectSlab::new(&mut slab);
+
+        assert_eq!(poll(&mut select, &mut cx), Poll::Pending);
+        assert!(select.get_mut().remove(1).is_some());
+        shared[3].complete();
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready((3, 3)));
+        shared[2].complete();
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready((2, 2)));
+
+        let key = select.get_mut().insert(Probe(4, &shared[4])).ok().unwrap();
+        shared[4].complete();
+        assert_eq!(poll(&mut select, &mut cx), Poll::Ready((key, 4)));
+        assert_eq!(polls(&shared), [3, 1, 3, 2, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn select_all_polls_futures_pushed_while_pending() {
+        static WAKERS: SelectWakers<4> = SelectWakers::new();
+        let shared: [Shared; 8] = Default::default();
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut all = SelectAll::new(probes(&shared, 2)).with_wakers(&WAKERS);
+
+        assert_eq!(all.poll_next(&mut cx), Poll::Pending);
+        all.push(Probe(2, &shared[2])).unwrap();
+        shared[2].done.set(true);
+        // the pushed future was not woken, but is polled as it is new
+        assert_eq!(all.poll_next(&mut cx), Poll::Ready(Some(2)));
+        assert_eq!(all.poll_next(&mut cx), Poll::Pending);
+        assert_eq!(polls(&shared), [1, 1, 1, 0, 0, 0, 0, 0]);
+
+        shared[0].complete();
+        shared[1].complete();
+        assert_eq!(all.poll_next(&mut cx), Poll::Ready(Some(1)));
+        assert_eq!(all.poll_next(&mut cx), Poll::Ready(Some(0)));
+        assert_eq!(all.poll_next(&mut cx), Poll::Ready(None));
+        // the stream goes on after another push
+        all.push(Probe(3, &shared[3])).unwrap();
+        assert_eq!(all.poll_next(&mut cx), Poll::Pending);
+        assert_eq!(all.into_inner().len(), 1);
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn select_all_stream() {
+        use futures_core::Stream;
+
+        let shared: [Shared; 8] = Default::default();
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut all = SelectAll::new(probes(&shared, 3));
+
+        assert_eq!(all.size_hint(), (3, Some(3)));
+        assert_eq!(Pin::new(&mut all).poll_next(&mut cx), Poll::Pending);
+        shared[1].complete();
+        assert_eq!(Pin::new(&mut all).poll_next(&mut cx), Poll::Ready(Some(1)));
+        assert_eq!(all.size_hint(), (2, Some(2)));
+        shared[0].complete();
+        shared[2].complete();
+        assert_eq!(Pin::new(&mut all).poll_next(&mut cx), Poll::Ready(Some(2)));
+        assert_eq!(Pin::new(&mut all).poll_next(&mut cx), Poll::Ready(Some(0)));
+        assert_eq!(Pin::new(&mut all).poll_next(&mut cx), Poll::Ready(None));
+        assert_eq!(all.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn wakers_are_claimed_by_one_select() {
+        static WAKERS: SelectWakers<4> = SelectWakers::new();
+        let mut a = StaticVec::<Probe<'_>, 4>::new();
+        let mut b = StaticVec::<Probe<'_>, 4>::new();
+
+        let select = SelectVec::new(&mut a).with_wakers(&WAKERS);
+        assert!(panics(|| {
+            SelectVec::new(&mut b).indexed().with_wakers(&WAKERS);
+        }));
+        // claiming the same set again is fine
+        let select = select.with_wakers(&WAKERS);
+        drop(select);
+        SelectAll::new(b).with_wakers(&WAKERS);
+    }
+}
@@ -0,0 +1,241 @@
+//! Fixed-capacity sorted set.
+
+use core::borrow::Borrow;
+use core::ops::RangeBounds;
+use core::{fmt, slice};
+
+use crate::map::sorted_range;
+use crate::{CapacityError, IntoIter, StaticVec};
+
+/// Fixed-capacity set, keeping its values sorted in a [`StaticVec`].
+///
+/// Lookups are O(log n), inserts and removals are O(n) as they shift the values behind.
+pub struct StaticSet<T, const N: usize> {
+    data: StaticVec<T, N>,
+}
+
+impl<T, const N: usize> StaticSet<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            data: StaticVec::new(),
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    /// Returns the values in ascending order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.data.last()
+    }
+
+    pub fn pop_first(&mut self) -> Option<T> {
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(self.data.remove(0))
+    }
+
+    pub fn pop_last(&mut self) -> Option<T> {
+        self.data.pop()
+    }
+
+    /// Keeps only the values for which `f` returns `true`.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.data.retain(f)
+    }
+}
+
+impl<T: Ord, const N: usize> StaticSet<T, N> {
+    /// Creates a set from a vec of values, dropping duplicates.
+    pub fn from_vec(mut data: StaticVec<T, N>) -> Self {
+        data.sort_unstable();
+        data.dedup();
+        Self { data }
+    }
+
+    fn search<Q>(&self, value: &Q) -> Result<usize, usize>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.data.binary_search_by(|it| it.borrow().cmp(value))
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search(value).is_ok()
+    }
+
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let idx = self.search(value).ok()?;
+        Some(&self.data[idx])
+    }
+
+    /// Inserts `value`, returning whether it was newly inserted.
+    ///
+    /// If the value is new and the set is full, it is handed back in the error.
+    pub fn insert(&mut self, value: T) -> Result<bool, CapacityError<T>> {
+        match self.search(&value) {
+            Ok(_) => Ok(false),
+            Err(idx) => {
+                self.data.try_insert(idx, value)?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Removes `value`, returning whether it was present.
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.take(value).is_some()
+    }
+
+    /// Removes `value` and returns it, if present.
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let idx = self.search(value).ok()?;
+        Some(self.data.remove(idx))
+    }
+
+    /// Returns an iterator over the values within `range`, in ascending order.
+    ///
+    /// Panics like `BTreeSet::range` if the start of the range is greater than its end, or if
+    /// both are equal and excluded.
+    pub fn range<Q, R>(&self, range: R) -> slice::Iter<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let slice = self.data.as_slice();
+        slice[sorted_range(slice, &range, |it, q| it.borrow().cmp(q))].iter()
+    }
+}
+
+impl<T, const N: usize> Default for StaticSet<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for StaticSet<T, N> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for StaticSet<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for StaticSet<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for StaticSet<T, N> {}
+
+impl<T: Ord, const N: usize> From<StaticVec<T, N>> for StaticSet<T, N> {
+    fn from(data: StaticVec<T, N>) -> Self {
+        Self::from_vec(data)
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a StaticSet<T, N> {
+    type Item = &'a T;
+
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, const N: usize> IntoIterator for StaticSet<T, N> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ops::Bound;
+
+    use super::*;
+    use crate::test_util::panics;
+
+    #[test]
+    fn keeps_values_sorted_and_unique() {
+        let mut set = StaticSet::<u8, 4>::from_vec(StaticVec::from_array([5, 1, 5, 3]));
+        assert_eq!(set.as_slice(), &[1, 3, 5]);
+        assert_eq!(set.insert(2), Ok(true));
+        assert_eq!(set.insert(3), Ok(false));
+        assert_eq!(set.insert(9).unwrap_err().into_inner(), 9);
+        assert_eq!(set.as_slice(), &[1, 2, 3, 5]);
+        assert_eq!(set.take(&2), Some(2));
+        assert!(!set.remove(&2));
+        assert_eq!((set.pop_first(), set.pop_last()), (Some(1), Some(5)));
+        assert!(set.contains(&3) && set.len() == 1);
+    }
+
+    #[test]
+    fn range_compares_bounds() {
+        let set = StaticSet::<u8, 4>::from_vec(StaticVec::from_array([10, 30, 50, 70]));
+        assert!(set.range(20..=50).eq(&[30, 50]));
+        assert!(set.range(35..45).eq(&[]));
+        assert!(panics(
+            || _ = set.range((Bound::Included(45), Bound::Excluded(35)))
+        ));
+    }
+}
@@ -0,0 +1,412 @@
+//! Fixed-capacity slab with stable keys.
+
+use core::iter::{Enumerate, FusedIterator};
+use core::{fmt, mem, slice};
+
+use crate::{CapacityError, StaticVec, StaticVecError};
+
+#[derive(Clone)]
+enum Slot<T> {
+    /// Holds the key of the next vacant slot, or the end of the storage.
+    Vacant(usize),
+    Occupied(T),
+}
+
+/// Fixed-capacity slab, handing out keys that stay valid until their value is removed.
+///
+/// Inserts and removals are O(1). Removed slots are threaded into a free list and reused by the
+/// following inserts, so a key may refer to a different value once its own value was removed.
+pub struct StaticSlab<T, const N: usize> {
+    entries: StaticVec<Slot<T>, N>,
+    len: usize,
+    // head of the free list, `entries.len()` if no slot is vacant
+    next: usize,
+}
+
+impl<T, const N: usize> StaticSlab<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            entries: StaticVec::new(),
+            len: 0,
+            next: 0,
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.len = 0;
+        self.next = 0;
+    }
+
+    pub fn contains(&self, key: usize) -> bool {
+        matches!(self.entries.get(key), Some(Slot::Occupied(_)))
+    }
+
+    pub fn get(&self, key: usize) -> Option<&T> {
+        match self.entries.get(key) {
+            Some(Slot::Occupied(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        match self.entries.get_mut(key) {
+            Some(Slot::Occupied(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Inserts `value` and returns its key.
+    ///
+    /// If the slab is full, the value is handed back in the error.
+    pub fn insert(&mut self, value: T) -> Result<usize, CapacityError<T>> {
+        if self.is_full() {
+            return Err(CapacityError::new(value));
+        }
+        let key = self.next;
+        self.insert_at(key, value);
+        Ok(key)
+    }
+
+    /// Returns a handle to the slot the next insert goes into, so its key can be known before
+    /// the value is created.
+    pub fn vacant_entry(&mut self) -> Result<VacantEntry<'_, T, N>, StaticVecError> {
+        if self.is_full() {
+            return Err(StaticVecError::CapacityExceeded);
+        }
+        Ok(VacantEntry {
+            key: self.next,
+            slab: self,
+        })
+    }
+
+    /// Stores `value` in the vacant slot at the head of the free list, which must be `key`.
+    fn insert_at(&mut self, key: usize, value: T) {
+        if key == self.entries.len() {
+            //cannot fail, as the slab is not full
+            let _ = self.entries.push(Slot::Occupied(value));
+            self.next = key + 1;
+        } else {
+            match mem::replace(&mut self.entries.as_mut_slice()[key], Slot::Occupied(value)) {
+                Slot::Vacant(next) => self.next = next,
+                Slot::Occupied(_) => unreachable!("free list points to an occupied slot"),
+            }
+        }
+        self.len += 1;
+    }
+
+    /// Removes the value under `key` and returns it, leaving the key free for reuse.
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        let slot = self.entries.as_mut_slice().get_mut(key)?;
+        if let Slot::Vacant(_) = slot {
+            return None;
+        }
+        match mem::replace(slot, Slot::Vacant(self.next)) {
+            Slot::Occupied(value) => {
+                self.next = key;
+                self.len -= 1;
+                Some(value)
+            }
+            Slot::Vacant(_) => unreachable!(),
+        }
+    }
+
+    /// Keeps only the values for which `f` returns `true`.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize, &mut T) -> bool,
+    {
+        for key in 0..self.entries.len() {
+            let keep = match &mut self.entries.as_mut_slice()[key] {
+                Slot::Occupied(value) => f(key, value),
+                Slot::Vacant(_) => true,
+            };
+            if !keep {
+                self.remove(key);
+            }
+        }
+    }
+
+    /// Returns an iterator over the keys and values, in the key order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.entries.iter().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the keys and mutable values, in the key order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            inner: self.entries.iter_mut().enumerate(),
+            len: self.len,
+        }
+    }
+}
+
+/// Handle to a vacant slot of a [`StaticSlab`], returned by [`StaticSlab::vacant_entry`].
+pub struct VacantEntry<'a, T, const N: usize> {
+    slab: &'a mut StaticSlab<T, N>,
+    key: usize,
+}
+
+impl<'a, T, const N: usize> VacantEntry<'a, T, N> {
+    /// Returns the key the value will be inserted under.
+    pub fn key(&self) -> usize {
+        self.key
+    }
+
+    /// Inserts `value` into the slot and returns a reference to it.
+    pub fn insert(self, value: T) -> &'a mut T {
+        self.slab.insert_at(self.key, value);
+        //cannot fail, as the value was just inserted
+        self.slab.get_mut(self.key).unwrap()
+    }
+}
+
+impl<'a, T, const N: usize> fmt::Debug for VacantEntry<'a, T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VacantEntry").field(&self.key).finish()
+    }
+}
+
+/// Iterator over the keys and values of a [`StaticSlab`].
+pub struct Iter<'a, T> {
+    inner: Enumerate<slice::Iter<'a, Slot<T>>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (key, slot) in self.inner.by_ref() {
+            if let Slot::Occupied(value) = slot {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some((key, slot)) = self.inner.next_back() {
+            if let Slot::Occupied(value) = slot {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            len: self.len,
+        }
+    }
+}
+
+/// Iterator over the keys and mutable values of a [`StaticSlab`].
+pub struct IterMut<'a, T> {
+    inner: Enumerate<slice::IterMut<'a, Slot<T>>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (usize, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (key, slot) in self.inner.by_ref() {
+            if let Slot::Occupied(value) = slot {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some((key, slot)) = self.inner.next_back() {
+            if let Slot::Occupied(value) = slot {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
+impl<T, const N: usize> Default for StaticSlab<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for StaticSlab<T, N> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            len: self.len,
+            next: self.next,
+        }
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for StaticSlab<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const N: usize> core::ops::Index<usize> for StaticSlab<T, N> {
+    type Output = T;
+
+    fn index(&self, key: usize) -> &Self::Output {
+        self.get(key).expect("invalid slab key")
+    }
+}
+
+impl<T, const N: usize> core::ops::IndexMut<usize> for StaticSlab<T, N> {
+    fn index_mut(&mut self, key: usize) -> &mut Self::Output {
+        self.get_mut(key).expect("invalid slab key")
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a StaticSlab<T, N> {
+    type Item = (usize, &'a T);
+
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut StaticSlab<T, N> {
+    type Item = (usize, &'a mut T);
+
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use super::*;
+    use crate::test_util::DropCounter;
+
+    fn keys<T, const N: usize>(slab: &StaticSlab<T, N>) -> StaticVec<usize, N> {
+        let mut keys = StaticVec::new();
+        for (key, _) in slab {
+            keys.push(key).unwrap();
+        }
+        keys
+    }
+
+    #[test]
+    fn reuses_removed_keys_last_first() {
+        let mut slab = StaticSlab::<u8, 4>::new();
+        for i in 0..4 {
+            assert_eq!(slab.insert(i * 10).ok(), Some(i as usize));
+        }
+        assert_eq!(slab.insert(40).unwrap_err().into_inner(), 40);
+        assert_eq!(slab.remove(1), Some(10));
+        assert_eq!(slab.remove(1), None);
+        assert_eq!(slab.remove(3), Some(30));
+        assert_eq!(slab.remove(4), None);
+        // the other keys still refer to their values
+        assert_eq!((slab[0], slab[2], slab.len()), (0, 20, 2));
+        assert!(!slab.contains(1) && slab.get(3).is_none());
+
+        assert_eq!(slab.insert(50).ok(), Some(3));
+        let entry = slab.vacant_entry().unwrap();
+        assert_eq!(entry.key(), 1);
+        *entry.insert(60) += 1;
+        assert_eq!((slab[1], slab[3]), (61, 50));
+        assert!(slab.is_full() && slab.vacant_entry().is_err());
+    }
+
+    #[test]
+    fn iterates_occupied_slots() {
+        let mut slab = StaticSlab::<u8, 8>::new();
+        for i in 0..6 {
+            slab.insert(i).unwrap();
+        }
+        slab.remove(0);
+        slab.remove(4);
+        let mut iter = slab.iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some((1, &1)));
+        assert_eq!(iter.next_back(), Some((5, &5)));
+        assert_eq!(iter.next_back(), Some((3, &3)));
+        assert_eq!(iter.len(), 1);
+        for (key, value) in &mut slab {
+            *value += key as u8;
+        }
+        assert_eq!((slab[2], slab[5]), (4, 10));
+    }
+
+    #[test]
+    fn retain_and_clear_drop_values() {
+        let drops = Cell::new(0);
+        let mut slab = StaticSlab::<_, 8>::new();
+        for _ in 0..6 {
+            slab.insert(DropCounter::new(&drops)).unwrap();
+        }
+        slab.retain(|key, _| key % 3 != 0);
+        assert_eq!(drops.get(), 2);
+        assert_eq!(keys(&slab).as_slice(), &[1, 2, 4, 5]);
+        // the freed keys are reused before the slab grows
+        assert_eq!(slab.insert(DropCounter::new(&drops)).ok(), Some(3));
+        assert_eq!(keys(&slab).as_slice(), &[1, 2, 3, 4, 5]);
+        slab.clear();
+        assert_eq!(drops.get(), 7);
+        assert_eq!(slab.insert(DropCounter::new(&drops)).ok(), Some(0));
+        assert_eq!(keys(&slab).as_slice(), &[0]);
+        drop(slab);
+        assert_eq!(drops.get(), 8);
+    }
+}
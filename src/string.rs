@@ -0,0 +1,375 @@
+use core::{fmt, ops, str};
+
+use crate::{CapacityError, StaticVec, StaticVecError};
+
+/// Fixed-capacity UTF-8 string, stored in a `StaticVec<u8, N>`.
+#[derive(Clone, Default)]
+pub struct StaticString<const N: usize> {
+    vec: StaticVec<u8, N>,
+}
+
+/// Formats into a [`StaticString`], returning `Err(StaticVecError::CapacityExceeded)` instead of
+/// panicking if the output does not fit. The capacity is inferred from the context.
+///
+/// ```
+/// use simplestaticvec::{format_static, StaticString};
+///
+/// let s: StaticString<16> = format_static!("{}-{}", 1, 2).unwrap();
+/// assert_eq!(s, "1-2");
+/// ```
+#[macro_export]
+macro_rules! format_static {
+    ($($arg:tt)*) => {{
+        let mut s = $crate::StaticString::new();
+        match ::core::fmt::Write::write_fmt(&mut s, ::core::format_args!($($arg)*)) {
+            ::core::result::Result::Ok(()) => ::core::result::Result::Ok(s),
+            ::core::result::Result::Err(_) => {
+                ::core::result::Result::Err($crate::StaticVecError::CapacityExceeded)
+            }
+        }
+    }};
+}
+
+impl<const N: usize> StaticString<N> {
+    pub const fn new() -> Self {
+        Self {
+            vec: StaticVec::new(),
+        }
+    }
+
+    /// Converts a vec of bytes into a string, if the bytes are valid UTF-8.
+    pub fn from_utf8(vec: StaticVec<u8, N>) -> Result<Self, str::Utf8Error> {
+        str::from_utf8(&vec)?;
+        Ok(Self { vec })
+    }
+
+    pub const fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn as_str(&self) -> &str {
+        //safe as we ensure that the bytes are valid UTF-8
+        unsafe { str::from_utf8_unchecked(&self.vec) }
+    }
+
+    pub fn as_mut_str(&mut self) -> &mut str {
+        //safe as we ensure that the bytes are valid UTF-8
+        unsafe { str::from_utf8_unchecked_mut(&mut self.vec) }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.vec
+    }
+
+    pub fn into_bytes(self) -> StaticVec<u8, N> {
+        self.vec
+    }
+
+    pub fn push(&mut self, ch: char) -> Result<(), CapacityError<char>> {
+        let mut buf = [0; 4];
+        if self.try_push_str(ch.encode_utf8(&mut buf)).is_err() {
+            return Err(CapacityError::new(ch));
+        }
+        Ok(())
+    }
+
+    /// Appends `s`.
+    ///
+    /// Panics if `s` does not fit.
+    pub fn push_str(&mut self, s: &str) {
+        if self.try_push_str(s).is_err() {
+            panic!("string exceeds capacity {}", N);
+        }
+    }
+
+    /// Appends `s`, or returns it back without modifying the string if it does not fit.
+    pub fn try_push_str<'a>(&mut self, s: &'a str) -> Result<(), CapacityError<&'a str>> {
+        if s.len() > N - self.len() {
+            return Err(CapacityError::new(s));
+        }
+        //cannot fail, as the capacity was checked above
+        self.vec
+            .try_extend_from_slice(s.as_bytes())
+            .map_err(|_| CapacityError::new(s))
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        let ch = self.chars().next_back()?;
+        self.vec.truncate(self.len() - ch.len_utf8());
+        Some(ch)
+    }
+
+    /// Shortens the string to `new_len` bytes. Does nothing if `new_len` is greater than the
+    /// current length.
+    ///
+    /// Panics if `new_len` does not lie on a char boundary.
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len < self.len() {
+            assert!(self.is_char_boundary(new_len));
+            self.vec.truncate(new_len);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.vec.clear();
+    }
+
+    /// Inserts `ch` at byte position `idx`.
+    ///
+    /// Panics if `idx` does not lie on a char boundary or if `ch` does not fit.
+    pub fn insert(&mut self, idx: usize, ch: char) {
+        if self.try_insert(idx, ch).is_err() {
+            panic!("string exceeds capacity {}", N);
+        }
+    }
+
+    /// Inserts `ch` at byte position `idx`, or returns it back if it does not fit.
+    ///
+    /// Panics if `idx` does not lie on a char boundary.
+    pub fn try_insert(&mut self, idx: usize, ch: char) -> Result<(), CapacityError<char>> {
+        let mut buf = [0; 4];
+        if self.try_insert_str(idx, ch.encode_utf8(&mut buf)).is_err() {
+            return Err(CapacityError::new(ch));
+        }
+        Ok(())
+    }
+
+    /// Inserts `s` at byte position `idx`, or returns it back if it does not fit.
+    ///
+    /// Panics if `idx` does not lie on a char boundary.
+    pub fn try_insert_str<'a>(
+        &mut self,
+        idx: usize,
+        s: &'a str,
+    ) -> Result<(), CapacityError<&'a str>> {
+        assert!(self.is_char_boundary(idx));
+        self.vec
+            .try_replace_range(idx..idx, s.as_bytes())
+            .map_err(|_| CapacityError::new(s))
+    }
+
+    /// Removes the char at byte position `idx` and returns it.
+    ///
+    /// Panics if `idx` does not lie on a char boundary or is out of bounds.
+    pub fn remove(&mut self, idx: usize) -> char {
+        let ch = match self[idx..].chars().next() {
+            Some(ch) => ch,
+            None => panic!("cannot remove a char from the end of a string"),
+        };
+        self.vec.drain(idx..idx + ch.len_utf8());
+        ch
+    }
+}
+
+impl<const N: usize> ops::Deref for StaticString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> ops::DerefMut for StaticString<N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for StaticString<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for StaticString<N> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<const N: usize> core::borrow::Borrow<str> for StaticString<N> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> fmt::Write for StaticString<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.try_push_str(s).map_err(|_| fmt::Error)
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.push(c).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const N: usize> fmt::Display for StaticString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Debug for StaticString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> str::FromStr for StaticString<N> {
+    type Err = StaticVecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ret = Self::new();
+        ret.try_push_str(s)?;
+        Ok(ret)
+    }
+}
+
+impl<'a, const N: usize> TryFrom<&'a str> for StaticString<N> {
+    type Error = CapacityError<&'a str>;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let mut ret = Self::new();
+        ret.try_push_str(value)?;
+        Ok(ret)
+    }
+}
+
+impl<const N: usize, const M: usize> PartialEq<StaticString<M>> for StaticString<N> {
+    fn eq(&self, other: &StaticString<M>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for StaticString<N> {}
+
+impl<const N: usize> PartialEq<str> for StaticString<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a, const N: usize> PartialEq<&'a str> for StaticString<N> {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize> PartialEq<StaticString<N>> for str {
+    fn eq(&self, other: &StaticString<N>) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl<const N: usize> PartialEq<StaticString<N>> for &str {
+    fn eq(&self, other: &StaticString<N>) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl<const N: usize, const M: usize> PartialOrd<StaticString<M>> for StaticString<N> {
+    fn partial_cmp(&self, other: &StaticString<M>) -> Option<core::cmp::Ordering> {
+        self.as_str().partial_cmp(other.as_str())
+    }
+}
+
+impl<const N: usize> PartialOrd<str> for StaticString<N> {
+    fn partial_cmp(&self, other: &str) -> Option<core::cmp::Ordering> {
+        self.as_str().partial_cmp(other)
+    }
+}
+
+impl<'a, const N: usize> PartialOrd<&'a str> for StaticString<N> {
+    fn partial_cmp(&self, other: &&'a str) -> Option<core::cmp::Ordering> {
+        self.as_str().partial_cmp(*other)
+    }
+}
+
+impl<const N: usize> Ord for StaticString<N> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<const N: usize> core::hash::Hash for StaticString<N> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::panics;
+
+    #[test]
+    fn push_and_pop_whole_chars() {
+        let mut s = StaticString::<4>::new();
+        s.push('a').unwrap();
+        s.push('é').unwrap();
+        // the 3 byte char does not fit into the 1 byte left
+        assert_eq!(s.push('€').unwrap_err().into_inner(), '€');
+        assert_eq!(s.try_push_str("bc").unwrap_err().into_inner(), "bc");
+        assert_eq!(s, "aé");
+        s.push('b').unwrap();
+        assert_eq!(s.pop(), Some('b'));
+        assert_eq!(s.pop(), Some('é'));
+        assert_eq!((s.as_str(), s.len()), ("a", 1));
+        assert!(panics(|| StaticString::<2>::new().push_str("abc")));
+    }
+
+    #[test]
+    fn edit_at_char_boundaries() {
+        let mut s: StaticString<8> = "aéb".parse().unwrap();
+        s.insert(1, '€');
+        assert_eq!(s, "a€éb");
+        assert_eq!(s.try_insert(0, '€').unwrap_err().into_inner(), '€');
+        assert_eq!(s.remove(1), '€');
+        assert_eq!(s, "aéb");
+        assert!(panics(|| s.clone().insert(2, 'x')));
+        assert!(panics(|| _ = s.clone().remove(2)));
+        assert!(panics(|| s.clone().truncate(2)));
+        s.truncate(8);
+        s.truncate(1);
+        assert_eq!(s, "a");
+    }
+
+    #[test]
+    fn conversions() {
+        let bytes = StaticVec::<u8, 4>::from_array(*b"ab");
+        assert_eq!(StaticString::from_utf8(bytes).unwrap(), "ab");
+        assert!(StaticString::from_utf8(StaticVec::<u8, 4>::from_array([0xff])).is_err());
+        assert!("abc".parse::<StaticString<2>>().is_err());
+        assert_eq!(
+            StaticString::<2>::try_from("abc").unwrap_err().into_inner(),
+            "abc"
+        );
+        assert_eq!(
+            StaticString::<4>::try_from("ab")
+                .unwrap()
+                .into_bytes()
+                .as_slice(),
+            b"ab"
+        );
+    }
+
+    #[test]
+    fn format_and_compare() {
+        let s: StaticString<8> = format_static!("{}+{}", 1, 22).unwrap();
+        assert_eq!(s, "1+22");
+        let full: Result<StaticString<3>, _> = format_static!("{}+{}", 1, 22);
+        assert_eq!(full.unwrap_err(), StaticVecError::CapacityExceeded);
+
+        let other = StaticString::<16>::try_from("1+3").unwrap();
+        assert!(s == StaticString::<4>::try_from("1+22").unwrap());
+        assert!(s < other && s > "1");
+    }
+}
@@ -0,0 +1,65 @@
+//! Element types for testing the drop and panic safety of the containers.
+
+use core::cell::Cell;
+
+use crate::StaticVec;
+
+/// Counts its drops, and panics when dropped if created by [`DropCounter::panicking`].
+#[derive(Debug)]
+pub(crate) struct DropCounter<'a> {
+    drops: &'a Cell<usize>,
+    panic: bool,
+}
+
+impl<'a> DropCounter<'a> {
+    pub(crate) fn new(drops: &'a Cell<usize>) -> Self {
+        Self {
+            drops,
+            panic: false,
+        }
+    }
+
+    pub(crate) fn panicking(drops: &'a Cell<usize>) -> Self {
+        Self { drops, panic: true }
+    }
+}
+
+impl<'a> Clone for DropCounter<'a> {
+    /// Clones never panic on drop.
+    fn clone(&self) -> Self {
+        Self::new(self.drops)
+    }
+}
+
+impl<'a> Drop for DropCounter<'a> {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+        if self.panic {
+            panic!("drop panicked");
+        }
+    }
+}
+
+/// Runs `f`, returning whether it panicked.
+pub(crate) fn panics<F: FnOnce()>(f: F) -> bool {
+    extern crate std;
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).is_err()
+}
+
+/// Returns a vec of `n` counters, keyed by their index.
+pub(crate) fn counters(drops: &Cell<usize>, n: u8) -> StaticVec<(u8, DropCounter<'_>), 8> {
+    let mut vec = StaticVec::new();
+    for i in 0..n {
+        vec.push((i, DropCounter::new(drops))).unwrap();
+    }
+    vec
+}
+
+/// Returns the keys of the counters in `vec`.
+pub(crate) fn keys(vec: &StaticVec<(u8, DropCounter<'_>), 8>) -> StaticVec<u8, 8> {
+    let mut keys = StaticVec::new();
+    for it in vec.iter() {
+        keys.push(it.0).unwrap();
+    }
+    keys
+}
@@ -0,0 +1,170 @@
+use core::fmt;
+
+use crate::{StaticVec, StaticVecError};
+
+/// Appends binary encoded data to the end of a `StaticVec<u8, N>`.
+///
+/// Every write is all-or-nothing: if the data does not fit, `CapacityExceeded` is returned and
+/// the vec is left unchanged.
+#[derive(Debug)]
+pub struct ByteWriter<'a, const N: usize> {
+    vec: &'a mut StaticVec<u8, N>,
+}
+
+macro_rules! write_int {
+    ($($ty:ty => $le:ident, $be:ident;)*) => {
+        $(
+            pub fn $le(&mut self, value: $ty) -> Result<(), StaticVecError> {
+                self.write_all(&value.to_le_bytes())
+            }
+
+            pub fn $be(&mut self, value: $ty) -> Result<(), StaticVecError> {
+                self.write_all(&value.to_be_bytes())
+            }
+        )*
+    };
+}
+
+impl<'a, const N: usize> ByteWriter<'a, N> {
+    pub fn new(vec: &'a mut StaticVec<u8, N>) -> Self {
+        Self { vec }
+    }
+
+    /// Number of bytes in the underlying vec, i.e. the position of the next write.
+    pub fn position(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// Number of bytes that can still be written.
+    pub fn remaining(&self) -> usize {
+        N - self.vec.len()
+    }
+
+    pub fn write_all(&mut self, buf: &[u8]) -> Result<(), StaticVecError> {
+        if buf.len() > self.remaining() {
+            return Err(StaticVecError::CapacityExceeded);
+        }
+        self.vec.try_extend_from_slice(buf)
+    }
+
+    pub fn write_u8(&mut self, value: u8) -> Result<(), StaticVecError> {
+        self.vec.push(value).map_err(StaticVecError::from)
+    }
+
+    pub fn write_i8(&mut self, value: i8) -> Result<(), StaticVecError> {
+        self.write_u8(value as u8)
+    }
+
+    write_int! {
+        u16 => write_u16_le, write_u16_be;
+        u32 => write_u32_le, write_u32_be;
+        u64 => write_u64_le, write_u64_be;
+        i16 => write_i16_le, write_i16_be;
+        i32 => write_i32_le, write_i32_be;
+        i64 => write_i64_le, write_i64_be;
+    }
+
+    /// Writes `value` as an unsigned LEB128 varint, taking 1 to 10 bytes.
+    pub fn write_varint(&mut self, mut value: u64) -> Result<(), StaticVecError> {
+        let mut buf = [0; 10];
+        let mut len = 0;
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                buf[len] = byte;
+                len += 1;
+                break;
+            }
+            buf[len] = byte | 0x80;
+            len += 1;
+        }
+        self.write_all(&buf[..len])
+    }
+
+    /// Writes `value` zigzag encoded as an unsigned LEB128 varint, so that small negative values
+    /// take few bytes too.
+    pub fn write_varint_signed(&mut self, value: i64) -> Result<(), StaticVecError> {
+        self.write_varint(((value << 1) ^ (value >> 63)) as u64)
+    }
+}
+
+impl<'a, const N: usize> fmt::Write for ByteWriter<'a, N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_all(s.as_bytes()).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const N: usize> StaticVec<u8, N> {
+    /// Returns a writer that appends binary encoded data to the vec.
+    pub fn writer(&mut self) -> ByteWriter<'_, N> {
+        ByteWriter::new(self)
+    }
+}
+
+impl<const N: usize> fmt::Write for StaticVec<u8, N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.writer().write_str(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+
+    use super::*;
+
+    #[test]
+    fn writes_byte_orders() {
+        let mut vec = StaticVec::<u8, 16>::new();
+        let mut writer = vec.writer();
+        writer.write_u16_le(0x0102).unwrap();
+        writer.write_u32_be(0x0304_0506).unwrap();
+        writer.write_i16_be(-2).unwrap();
+        writer.write_i8(-1).unwrap();
+        assert_eq!((writer.position(), writer.remaining()), (9, 7));
+        assert_eq!(vec.as_slice(), &[2, 1, 3, 4, 5, 6, 0xff, 0xfe, 0xff]);
+    }
+
+    #[test]
+    fn writes_varints() {
+        let mut vec = StaticVec::<u8, 16>::new();
+        let mut writer = vec.writer();
+        writer.write_varint(1).unwrap();
+        writer.write_varint(300).unwrap();
+        writer.write_varint_signed(-1).unwrap();
+        writer.write_varint_signed(-65).unwrap();
+        assert_eq!(vec.as_slice(), &[1, 0xac, 0x02, 1, 0x81, 0x01]);
+
+        let mut vec = StaticVec::<u8, 10>::new();
+        vec.writer().write_varint(u64::MAX).unwrap();
+        assert_eq!(
+            vec.as_slice(),
+            &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 1]
+        );
+    }
+
+    #[test]
+    fn failed_writes_leave_vec_unchanged() {
+        let mut vec = StaticVec::<u8, 4>::from_array([9]);
+        let mut writer = vec.writer();
+        assert_eq!(
+            writer.write_u32_le(0),
+            Err(StaticVecError::CapacityExceeded)
+        );
+        assert_eq!(
+            writer.write_varint(u64::MAX),
+            Err(StaticVecError::CapacityExceeded)
+        );
+        assert!(write!(writer, "{}", 1234).is_err());
+        writer.write_u16_be(0x0102).unwrap();
+        writer.write_u8(3).unwrap();
+        assert_eq!(writer.write_u8(4), Err(StaticVecError::CapacityExceeded));
+        assert_eq!(vec.as_slice(), &[9, 1, 2, 3]);
+
+        let mut vec = StaticVec::<u8, 8>::new();
+        let name = "ab";
+        write!(vec, "{}-{}", 12, name).unwrap();
+        assert_eq!(vec.as_slice(), b"12-ab");
+    }
+}